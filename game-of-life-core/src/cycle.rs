@@ -0,0 +1,190 @@
+//! Detecting when a pattern starts repeating itself.
+
+use std::collections::HashMap;
+
+use crate::{
+    grid::{BoundingBox, Cell},
+    GameOfLife, Universe,
+};
+
+/// The sorted cells of a generation which are not dead, relative to a position.
+type State = Vec<((i64, i64), Cell)>;
+
+/// How a pattern repeats once it has settled.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Cycle {
+    /// The pattern no longer changes, from generation `start` onward.
+    /// A pattern which died out is a still life as well.
+    StillLife { start: u64 },
+    /// The pattern returns to the same cells every `period` generations,
+    /// from generation `start` onward.
+    Oscillator { start: u64, period: u64 },
+    /// The pattern returns to the same shape every `period` generations,
+    /// moved by `dx` and `dy` cells, from generation `start` onward.
+    /// Only found on universes without edges.
+    Spaceship { start: u64, period: u64, dx: i64, dy: i64 },
+}
+
+impl Cycle {
+    /// Returns the generation the cycle began at.
+    pub fn start(&self) -> u64 {
+        match *self {
+            Cycle::StillLife { start } | Cycle::Oscillator { start, .. } | Cycle::Spaceship { start, .. } => start,
+        }
+    }
+    /// Returns the number of generations before the pattern repeats.
+    pub fn period(&self) -> u64 {
+        match *self {
+            Cycle::StillLife { .. } => 1,
+            Cycle::Oscillator { period, .. } | Cycle::Spaceship { period, .. } => period,
+        }
+    }
+}
+
+impl<T: Universe> GameOfLife<T> {
+    /// Advances the game until a generation repeats an earlier one,
+    /// or the game reaches generation `max_generation`.
+    /// The game is left at the generation the repeat was found.
+    ///
+    /// Every generation is remembered, so on a universe without edges
+    /// the cells are first moved to the top left corner of their bounding box
+    /// which lets a spaceship be found by its shape repeating.
+    ///
+    /// Returns None if no repeat was found by `max_generation`.
+    pub fn find_cycle(&mut self, max_generation: u64) -> Option<Cycle> {
+        let mut seen: HashMap<State, (u64, (i64, i64))> = HashMap::new();
+        loop {
+            let (state, position) = self.state();
+            if let Some(&(start, (x, y))) = seen.get(&state) {
+                let period = self.generation - start;
+                let (dx, dy) = (position.0.wrapping_sub(x), position.1.wrapping_sub(y));
+                return Some(match (period, dx, dy) {
+                    (1, 0, 0) => Cycle::StillLife { start },
+                    (_, 0, 0) => Cycle::Oscillator { start, period },
+                    _ => Cycle::Spaceship { start, period, dx, dy },
+                });
+            }
+            if self.generation >= max_generation {
+                return None;
+            }
+            seen.insert(state, (self.generation, position));
+            self.next();
+        }
+    }
+    /// Returns the sorted cells of the current generation which are not dead,
+    /// relative to the position they are measured from.
+    fn state(&self) -> (State, (i64, i64)) {
+        let mut cells = self.grid.cells();
+        let position = match BoundingBox::around(cells.iter().map(|&(position, _)| position)) {
+            Some(bounds) if self.grid.is_unbounded() => (bounds.min_x, bounds.min_y),
+            _ => (0, 0),
+        };
+        for ((x, y), _) in cells.iter_mut() {
+            *x = x.wrapping_sub(position.0);
+            *y = y.wrapping_sub(position.1);
+        }
+        cells.sort_unstable();
+        (cells, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{
+        grid::{dynamic::DynGrid, sparse::SparseGrid, topology::Topology, Cell, Grid, UnboundedGrid},
+        hashlife::HashLife,
+        tests::*,
+    };
+
+    const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    #[test]
+    fn still_life_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+            game.grid_mut().set(x, y, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        assert_eq!(Some(Cycle::StillLife { start: 0 }), game.find_cycle(10));
+        assert_eq!(1, game.generation());
+    }
+    /// Tests a pattern which dies out is a still life from the generation it died.
+    #[test]
+    fn died_out_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        game.grid_mut().set(1, 1, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        assert_eq!(Some(Cycle::StillLife { start: 1 }), game.find_cycle(10));
+    }
+    #[test]
+    fn blinker_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        for x in 1..=3 {
+            game.grid_mut().set(x, 2, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        let cycle = game.find_cycle(10).unwrap();
+        assert_eq!(Cycle::Oscillator { start: 0, period: 2 }, cycle);
+        assert_eq!(0, cycle.start());
+        assert_eq!(2, cycle.period());
+    }
+    /// Tests the cycle begins after the pattern settles,
+    /// here a T tetromino which becomes traffic lights at generation 9.
+    #[test]
+    fn traffic_light_test() {
+        let mut game = GameOfLife::from_grid(DynGrid::new(20, 20).unwrap());
+        for (x, y) in [(9, 9), (10, 9), (11, 9), (10, 10)] {
+            game.grid_mut().set(x, y, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        assert_eq!(Some(Cycle::Oscillator { start: 9, period: 2 }), game.find_cycle(100));
+    }
+    /// Tests a glider on a torus is an oscillator,
+    /// since it returns to the same cells after crossing the grid.
+    #[test]
+    fn torus_glider_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_topology(Topology::Torus);
+        for (x, y) in GLIDER {
+            game.grid_mut().set(x as usize, y as usize, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        let period = 4 * WIDTH as u64;
+        assert_eq!(Some(Cycle::Oscillator { start: 0, period }), game.find_cycle(1000));
+    }
+    #[test]
+    fn not_found_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_topology(Topology::Torus);
+        for (x, y) in GLIDER {
+            game.grid_mut().set(x as usize, y as usize, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        assert_eq!(None, game.find_cycle(20));
+        assert_eq!(20, game.generation());
+    }
+    #[test]
+    fn hashlife_spaceship_test() {
+        let mut game = GameOfLife::<HashLife>::default();
+        // Lightweight spaceship, travelling west.
+        for (x, y) in [(1, 0), (4, 0), (0, 1), (0, 2), (4, 2), (0, 3), (1, 3), (2, 3), (3, 3)] {
+            game.grid_mut().set(x, y, Cell::Alive);
+        }
+        game.grid_mut().update();
+        assert_eq!(Some(Cycle::Spaceship { start: 0, period: 4, dx: -2, dy: 0 }), game.find_cycle(100));
+    }
+    proptest! {
+        /// Tests a glider is found to be a spaceship
+        /// wherever it starts on an unbounded grid.
+        #[test]
+        fn glider_test(x in -1000i64..1000, y in -1000i64..1000) {
+            let mut game = GameOfLife::<SparseGrid>::default();
+            for (dx, dy) in GLIDER {
+                game.grid_mut().set(x + dx, y + dy, Cell::Alive);
+            }
+            game.grid_mut().update();
+            assert_eq!(Some(Cycle::Spaceship { start: 0, period: 4, dx: 1, dy: 1 }), game.find_cycle(100));
+            assert_eq!(4, game.generation());
+        }
+    }
+}
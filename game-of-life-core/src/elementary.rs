@@ -0,0 +1,272 @@
+//! One-dimensional elementary cellular automata, such as Rule 30, Rule 90 and Rule 110.
+//!
+//! Cells sit in a single row, and each generation a cell's next state
+//! is decided by itself and the cell on either side of it.
+//! The eight arrangements of three cells are numbered from `000` to `111`,
+//! reading alive cells as 1, and bit `n` of the Wolfram rule number
+//! is the next state of a cell whose arrangement is `n`.
+//!
+//! [`Elementary::write_to`] draws the generations one below the other into any [`Grid`],
+//! so the space-time diagram can be drawn and saved like any other grid.
+
+use crate::grid::{Cell, CellOutOfBoundsError, Grid};
+
+/// A rule of an elementary cellular automaton, numbered from 0 to 255.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct WolframRule {
+    number: u8,
+}
+
+impl WolframRule {
+    /// Rule 30, whose center column looks random.
+    pub const RULE_30: WolframRule = WolframRule::new(30);
+    /// Rule 90, which draws a Sierpiński triangle from a single cell.
+    pub const RULE_90: WolframRule = WolframRule::new(90);
+    /// Rule 110, which is Turing complete.
+    pub const RULE_110: WolframRule = WolframRule::new(110);
+
+    pub const fn new(number: u8) -> Self {
+        WolframRule { number }
+    }
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+    /// Returns the state a cell changes to during the next generation,
+    /// given the cell to its left, itself, and the cell to its right.
+    /// Only alive cells count as 1, dying cells count as dead.
+    pub fn next_cell(&self, left: Cell, cell: Cell, right: Cell) -> Cell {
+        let bit = |cell: Cell| u8::from(cell == Cell::Alive);
+        let arrangement = bit(left) << 2 | bit(cell) << 1 | bit(right);
+        if self.number >> arrangement & 1 == 1 {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+}
+
+/// What lies past the ends of the row.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Edges {
+    /// Everything past either end is dead.
+    #[default]
+    Fixed,
+    /// The ends join, so the cell past the right end is the leftmost cell.
+    Wrapping,
+}
+
+/// A row of cells evolved by a [`WolframRule`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Elementary {
+    cells: Vec<Cell>,
+    rule: WolframRule,
+    edges: Edges,
+    generation: u64,
+}
+
+impl Elementary {
+    /// Creates a row of `width` dead cells.
+    pub fn new(width: usize, rule: WolframRule) -> Self {
+        Elementary { cells: vec![Cell::Dead; width], rule, edges: Edges::default(), generation: 0 }
+    }
+    pub fn with_edges(self, edges: Edges) -> Self {
+        Elementary { edges, ..self }
+    }
+    pub fn rule(&self) -> &WolframRule {
+        &self.rule
+    }
+    pub fn edges(&self) -> Edges {
+        self.edges
+    }
+    pub fn width(&self) -> usize {
+        self.cells.len()
+    }
+    /// Returns how many generations have passed.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Returns the cell at `x`, if out of bounds, returns Err.
+    pub fn get(&self, x: usize) -> Result<Cell, CellOutOfBoundsError> {
+        self.cells.get(x).copied().ok_or(CellOutOfBoundsError::new(x, 0))
+    }
+    /// Changes the cell at `x` straight away, as there is no grid to update,
+    /// if out of bounds, returns Err.
+    pub fn set(&mut self, x: usize, cell: Cell) -> Result<(), CellOutOfBoundsError> {
+        let slot = self.cells.get_mut(x).ok_or(CellOutOfBoundsError::new(x, 0))?;
+        *slot = cell;
+        Ok(())
+    }
+    /// Advances the row a single generation.
+    pub fn next(&mut self) {
+        let width = self.width();
+        let beside = |x: Option<usize>| match (x, self.edges) {
+            (Some(x), _) if x < width => self.cells[x],
+            (_, Edges::Fixed) => Cell::Dead,
+            // The left end wraps to the rightmost cell, the right end to the leftmost.
+            (None, Edges::Wrapping) => self.cells[width - 1],
+            (Some(_), Edges::Wrapping) => self.cells[0],
+        };
+        let cells = (0..width)
+            .map(|x| self.rule.next_cell(beside(x.checked_sub(1)), self.cells[x], beside(Some(x + 1))))
+            .collect();
+        self.cells = cells;
+        self.generation += 1;
+    }
+    /// Draws the space-time diagram of the row onto the grid,
+    /// with the current generation on the top row, and each row below it the generation after.
+    /// The row itself does not change.
+    /// Cells past the right side of the grid are left out,
+    /// and columns of the grid past the end of the row are dead.
+    pub fn write_to(&self, grid: &mut impl Grid) {
+        let mut row = self.clone();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                // We know the cell is in bounds
+                // because we use the grid's width and height
+                // for coordinates.
+                grid.set(x, y, row.get(x).unwrap_or(Cell::Dead)).unwrap();
+            }
+            row.next();
+        }
+        grid.update();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::grid::dynamic::DynGrid;
+
+    #[test]
+    fn rule_30_table_test() {
+        use Cell::{Alive as X, Dead as O};
+        let rule = WolframRule::RULE_30;
+        assert_eq!(O, rule.next_cell(X, X, X));
+        assert_eq!(O, rule.next_cell(X, X, O));
+        assert_eq!(O, rule.next_cell(X, O, X));
+        assert_eq!(X, rule.next_cell(X, O, O));
+        assert_eq!(X, rule.next_cell(O, X, X));
+        assert_eq!(X, rule.next_cell(O, X, O));
+        assert_eq!(X, rule.next_cell(O, O, X));
+        assert_eq!(O, rule.next_cell(O, O, O));
+        assert_eq!(O, rule.next_cell(Cell::Dying(1), O, O));
+        assert_eq!(30, rule.number());
+    }
+    #[test]
+    fn out_of_bounds_test() {
+        let mut row = Elementary::new(4, WolframRule::RULE_30);
+        assert_eq!(Ok(Cell::Dead), row.get(3));
+        assert_eq!(Err(CellOutOfBoundsError::new(4, 0)), row.get(4));
+        assert_eq!(Err(CellOutOfBoundsError::new(4, 0)), row.set(4, Cell::Alive));
+        assert_eq!(Edges::Fixed, row.edges());
+    }
+    /// Tests the diagram of Rule 30 from a single cell.
+    #[test]
+    fn rule_30_diagram_test() {
+        let mut row = Elementary::new(9, WolframRule::RULE_30);
+        row.set(4, Cell::Alive).unwrap();
+        let mut grid = DynGrid::new(9, 5).unwrap();
+        row.write_to(&mut grid);
+        assert_eq!(
+            vec!["....#....", "...###...", "..##..#..", ".##.####.", "##..#...#"],
+            rows(&grid),
+        );
+        assert_eq!(0, row.generation());
+    }
+    /// Tests Rule 110 from a single cell grows to the left only.
+    #[test]
+    fn rule_110_diagram_test() {
+        let mut row = Elementary::new(8, WolframRule::RULE_110);
+        row.set(7, Cell::Alive).unwrap();
+        let mut grid = DynGrid::new(8, 5).unwrap();
+        row.write_to(&mut grid);
+        assert_eq!(
+            vec![".......#", "......##", ".....###", "....##.#", "...#####"],
+            rows(&grid),
+        );
+    }
+    /// Tests cells past the edges are dead with fixed edges,
+    /// and the cells at the other end with wrapping edges.
+    #[test]
+    fn edges_test() {
+        let mut fixed = Elementary::new(6, WolframRule::RULE_90);
+        fixed.set(0, Cell::Alive).unwrap();
+        let mut wrapping = fixed.clone().with_edges(Edges::Wrapping);
+        fixed.next();
+        wrapping.next();
+        assert_eq!(vec![Cell::Dead, Cell::Alive, Cell::Dead, Cell::Dead, Cell::Dead, Cell::Dead], fixed.cells);
+        assert_eq!(vec![Cell::Dead, Cell::Alive, Cell::Dead, Cell::Dead, Cell::Dead, Cell::Alive], wrapping.cells);
+        assert_eq!(1, wrapping.generation());
+    }
+    /// Tests the diagram leaves out cells past the grid, and fills columns past the row with dead cells.
+    #[test]
+    fn write_to_sizes_test() {
+        let mut row = Elementary::new(3, WolframRule::new(204));
+        row.set(0, Cell::Alive).unwrap();
+        row.set(2, Cell::Alive).unwrap();
+        let mut narrow = DynGrid::new(2, 2).unwrap();
+        row.write_to(&mut narrow);
+        assert_eq!(vec!["#.", "#."], rows(&narrow));
+        let mut wide = DynGrid::new(5, 2).unwrap();
+        row.write_to(&mut wide);
+        assert_eq!(vec!["#.#..", "#.#.."], rows(&wide));
+    }
+    #[test]
+    fn empty_row_test() {
+        let mut row = Elementary::new(0, WolframRule::RULE_30).with_edges(Edges::Wrapping);
+        row.next();
+        assert_eq!(0, row.width());
+    }
+    proptest! {
+        /// Tests the next state of every arrangement of three cells is the matching bit of the rule number.
+        #[test]
+        fn next_cell_test(number: u8, arrangement in 0u8..8) {
+            let cell = |bit: u8| if arrangement >> bit & 1 == 1 { Cell::Alive } else { Cell::Dead };
+            let expected = if number >> arrangement & 1 == 1 { Cell::Alive } else { Cell::Dead };
+            assert_eq!(expected, WolframRule::new(number).next_cell(cell(2), cell(1), cell(0)));
+        }
+        /// Tests Rule 90 draws Pascal's triangle modulo 2 from a single cell,
+        /// so a cell is alive where the matching binomial coefficient is odd.
+        #[test]
+        fn rule_90_sierpinski_test(generations in 0usize..32) {
+            let width = 2 * generations + 1;
+            let mut row = Elementary::new(width, WolframRule::RULE_90);
+            row.set(generations, Cell::Alive).unwrap();
+            for _ in 0..generations {
+                row.next();
+            }
+            for x in 0..width {
+                // The cell `x` from the left is the coefficient `generations` choose `x / 2` on even columns,
+                // which is odd when the bits of `x / 2` and `generations - x / 2` do not overlap.
+                let k = x / 2;
+                let odd = x % 2 == 0 && k & (generations - k) == 0;
+                assert_eq!(if odd { Cell::Alive } else { Cell::Dead }, row.get(x).unwrap(), "x = {}", x);
+            }
+        }
+        /// Tests rule 204, where each cell stays as it is, never changes a row with either edges.
+        #[test]
+        fn identity_rule_test(cells in prop::collection::vec(any::<bool>(), 0..40), wrapping: bool) {
+            let edges = if wrapping { Edges::Wrapping } else { Edges::Fixed };
+            let mut row = Elementary::new(cells.len(), WolframRule::new(204)).with_edges(edges);
+            for (x, &alive) in cells.iter().enumerate() {
+                row.set(x, if alive { Cell::Alive } else { Cell::Dead }).unwrap();
+            }
+            let expected = row.cells.clone();
+            row.next();
+            assert_eq!(expected, row.cells);
+        }
+    }
+
+    // Helper Functions
+    // These are called in tests above.
+
+    /// Returns each row of the grid as text, `#` for alive cells and `.` for dead ones.
+    fn rows(grid: &DynGrid) -> Vec<String> {
+        (0..grid.height())
+            .map(|y| {
+                (0..grid.width()).map(|x| if grid.get(x, y) == Ok(Cell::Alive) { '#' } else { '.' }).collect()
+            })
+            .collect()
+    }
+}
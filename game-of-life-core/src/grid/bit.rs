@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use crate::rule::Rule;
+
+use super::{neighborhood::Neighborhood, topology::Topology, Cell, CellOutOfBoundsError, Grid};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A grid which packs 64 cells into each `u64`, one bit per cell.
+/// Cells are stored row by row, where the cell at `(x, y)` is bit `x % 64`
+/// of word `x / 64` of row `y`.
+/// Bits past the width of the grid are always zero.
+///
+/// On a bounded grid or a torus, a generation is calculated for 64 cells at a time
+/// by adding the neighbor bits together with bitwise operations,
+/// other topologies, other neighborhoods, rules which are not [totalistic](Rule::is_totalistic)
+/// and rules with more than two [states](Rule::states) fall back to looking at the neighbors of each cell.
+///
+/// A bit only tells alive from dead, so dying cells are not packed,
+/// but kept apart by their position with their age.
+#[derive(Clone)]
+pub struct BitGrid<const WIDTH: usize, const HEIGHT: usize> {
+    cells: Vec<u64>,
+    // A bit is set in `changed` when the cell was set,
+    // and `change_to` holds what it was set to.
+    changed: Vec<u64>,
+    change_to: Vec<u64>,
+    // Dying cells, and the dying cells set since the last update.
+    dying: HashMap<(usize, usize), u8>,
+    change_dying: HashMap<(usize, usize), u8>,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Default for BitGrid<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        let words = Self::WORDS_PER_ROW * HEIGHT;
+        BitGrid {
+            cells: vec![0; words],
+            changed: vec![0; words],
+            change_to: vec![0; words],
+            dying: HashMap::new(),
+            change_dying: HashMap::new(),
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> BitGrid<WIDTH, HEIGHT> {
+    const WORDS_PER_ROW: usize = WIDTH.div_ceil(WORD_BITS);
+
+    /// Returns the index of the word holding the cell, and the bit of the cell within it.
+    fn position(&self, x: usize, y: usize) -> Result<(usize, u64), CellOutOfBoundsError> {
+        if x < WIDTH && y < HEIGHT {
+            Ok((y * Self::WORDS_PER_ROW + x / WORD_BITS, 1 << (x % WORD_BITS)))
+        } else {
+            Err(CellOutOfBoundsError::new(x, y))
+        }
+    }
+    /// Returns the bits which are within the width of the grid for the word at `index` of a row.
+    fn word_mask(index: usize) -> u64 {
+        let used = WIDTH - index * WORD_BITS;
+        if used >= WORD_BITS { u64::MAX } else { (1 << used) - 1 }
+    }
+    /// Returns word `i` of the row moved one cell east, so each cell holds its west neighbor.
+    fn west_neighbors(row: &[u64], i: usize, wrap: bool) -> u64 {
+        let carry = match i {
+            0 if wrap => {
+                let last = WIDTH - 1;
+                row[last / WORD_BITS] >> (last % WORD_BITS) & 1
+            },
+            0 => 0,
+            _ => row[i - 1] >> (WORD_BITS - 1),
+        };
+        (row[i] << 1 | carry) & Self::word_mask(i)
+    }
+    /// Returns word `i` of the row moved one cell west, so each cell holds its east neighbor.
+    fn east_neighbors(row: &[u64], i: usize, wrap: bool) -> u64 {
+        let carry = row.get(i + 1).map_or(0, |next| next & 1);
+        let mut shifted = row[i] >> 1 | carry << (WORD_BITS - 1);
+        let last = WIDTH - 1;
+        if wrap && i == last / WORD_BITS {
+            shifted |= (row[0] & 1) << (last % WORD_BITS);
+        }
+        shifted
+    }
+    /// Adds the eight neighbor bits of 64 cells at once with a tree of adders,
+    /// into a four bit count per cell, the lowest bit first.
+    fn add_neighbors([a, b, c, d, e, f, g, h]: [u64; 8]) -> [u64; 4] {
+        let full = |a: u64, b: u64, c: u64| (a ^ b ^ c, a & b | c & (a ^ b));
+        let half = |a: u64, b: u64| (a ^ b, a & b);
+        let (ones_a, twos_a) = full(a, b, c);
+        let (ones_b, twos_b) = full(d, e, f);
+        let (ones_c, twos_c) = half(g, h);
+        let (ones, twos_d) = full(ones_a, ones_b, ones_c);
+        let (twos_e, fours_a) = full(twos_a, twos_b, twos_c);
+        let (twos, fours_b) = half(twos_e, twos_d);
+        let (fours, eights) = half(fours_a, fours_b);
+        [ones, twos, fours, eights]
+    }
+    /// Returns the cells whose four bit count of neighbors is `count`.
+    fn with_count([ones, twos, fours, eights]: [u64; 4], count: usize) -> u64 {
+        let bit = |word: u64, set: usize| if set != 0 { word } else { !word };
+        bit(ones, count & 1) & bit(twos, count & 2) & bit(fours, count & 4) & bit(eights, count & 8)
+    }
+    /// Calculates the next generation 64 cells at a time.
+    fn next_generation_bitwise(&mut self, rule: &Rule, wrap: bool) {
+        let birth: [bool; 9] = std::array::from_fn(|count| rule.births(count));
+        let survival: [bool; 9] = std::array::from_fn(|count| rule.survives(count));
+        // The next generation is written over the cells set since the last update,
+        // as they are discarded by the next generation anyway.
+        let (cells, next) = (&self.cells, &mut self.change_to);
+        let row = |y: usize| &cells[y * Self::WORDS_PER_ROW..(y + 1) * Self::WORDS_PER_ROW];
+        for y in 0..HEIGHT {
+            let above = match y {
+                0 if wrap => Some(row(HEIGHT - 1)),
+                0 => None,
+                _ => Some(row(y - 1)),
+            };
+            let below = match y + 1 {
+                below if below < HEIGHT => Some(row(below)),
+                _ if wrap => Some(row(0)),
+                _ => None,
+            };
+            let middle = row(y);
+            for (i, &alive) in middle.iter().enumerate() {
+                // The three neighbors above or below each cell, none past the edge of the grid.
+                let three = |row: Option<&[u64]>| {
+                    row.map_or([0; 3], |row| [Self::west_neighbors(row, i, wrap), row[i], Self::east_neighbors(row, i, wrap)])
+                };
+                let [north_west, north, north_east] = three(above);
+                let [south_west, south, south_east] = three(below);
+                let west = Self::west_neighbors(middle, i, wrap);
+                let east = Self::east_neighbors(middle, i, wrap);
+                let sum = Self::add_neighbors([north_west, north, north_east, west, east, south_west, south, south_east]);
+                let mut born = 0;
+                let mut survived = 0;
+                for count in (0..=8).filter(|&count| birth[count] || survival[count]) {
+                    let matches = Self::with_count(sum, count);
+                    if birth[count] {
+                        born |= matches;
+                    }
+                    if survival[count] {
+                        survived |= matches;
+                    }
+                }
+                next[y * Self::WORDS_PER_ROW + i] = (!alive & born | alive & survived) & Self::word_mask(i);
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.change_to);
+        self.changed.fill(0);
+        self.change_dying.clear();
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Grid for BitGrid<WIDTH, HEIGHT> {
+    fn get(&self, x: usize, y: usize) -> Result<Cell, CellOutOfBoundsError> {
+        let (i, bit) = self.position(x, y)?;
+        if self.cells[i] & bit != 0 {
+            Ok(Cell::Alive)
+        } else {
+            Ok(self.dying.get(&(x, y)).map_or(Cell::Dead, |&age| Cell::Dying(age)))
+        }
+    }
+    fn set(&mut self, x: usize, y: usize, cell: Cell) -> Result<(), CellOutOfBoundsError> {
+        let (i, bit) = self.position(x, y)?;
+        self.changed[i] |= bit;
+        match cell {
+            Cell::Alive => self.change_to[i] |= bit,
+            Cell::Dead | Cell::Dying(_) => self.change_to[i] &= !bit,
+        }
+        match cell {
+            Cell::Dying(age) => self.change_dying.insert((x, y), age),
+            Cell::Alive | Cell::Dead => self.change_dying.remove(&(x, y)),
+        };
+        Ok(())
+    }
+    fn update(&mut self) {
+        // Every cell set since the last update stops dying, unless it was set to a dying state.
+        let changed = &self.changed;
+        self.dying.retain(|&(x, y), _| changed[y * Self::WORDS_PER_ROW + x / WORD_BITS] & 1 << (x % WORD_BITS) == 0);
+        self.dying.extend(self.change_dying.drain());
+        for ((cell, changed), change_to) in self.cells.iter_mut().zip(self.changed.iter_mut()).zip(&self.change_to) {
+            *cell = *cell & !*changed | change_to & *changed;
+            *changed = 0;
+        }
+    }
+    fn next_generation(&mut self, rule: &Rule, topology: Topology, neighborhood: &Neighborhood) {
+        match topology {
+            // The bitwise generation only tells alive from dead,
+            // so it cannot age dying cells, or keep them from being born.
+            _ if !rule.is_totalistic() || *neighborhood != Neighborhood::Moore || rule.states() > 2 || !self.dying.is_empty() => {
+                crate::evolve_each_cell(self, rule, topology, neighborhood);
+            },
+            Topology::Bounded => self.next_generation_bitwise(rule, false),
+            Topology::Torus => self.next_generation_bitwise(rule, true),
+            _ => crate::evolve_each_cell(self, rule, topology, neighborhood),
+        }
+    }
+    fn width(&self) -> usize {
+        WIDTH
+    }
+    fn height(&self) -> usize {
+        HEIGHT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::time::Instant;
+    use crate::{grid::{dynamic::DynGrid, obj::GameOfLifeGrid}, tests::*};
+
+    #[test]
+    fn grid_default_test() {
+        let grid = BitGrid::<WIDTH, HEIGHT>::default();
+        assert_eq!(WIDTH, grid.width());
+        assert_eq!(HEIGHT, grid.height());
+        assert_eq!(HEIGHT, grid.cells.len());
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                assert_eq!(Ok(Cell::Dead), grid.get(x, y));
+            }
+        }
+    }
+    #[test]
+    fn word_mask_test() {
+        assert_eq!(u64::MAX, BitGrid::<130, 1>::word_mask(0));
+        assert_eq!(u64::MAX, BitGrid::<130, 1>::word_mask(1));
+        assert_eq!(0b11, BitGrid::<130, 1>::word_mask(2));
+        assert_eq!(u64::MAX, BitGrid::<64, 1>::word_mask(0));
+    }
+    /// Tests cells are packed into the bit of the word their coordinates point to.
+    #[test]
+    fn packing_test() {
+        let mut grid = BitGrid::<100, 3>::default();
+        assert_eq!(6, grid.cells.len());
+        grid.set(70, 2, Cell::Alive).unwrap();
+        grid.update();
+        assert_eq!(1 << 6, grid.cells[5]);
+        assert_eq!(Ok(Cell::Alive), grid.get(70, 2));
+    }
+    /// Tests a cell set after the last update is replaced by the next generation,
+    /// the same as a grid which counts the neighbors of each cell.
+    #[test]
+    fn next_generation_discards_pending_test() {
+        let mut grid = BitGrid::<WIDTH, HEIGHT>::default();
+        grid.set(1, 1, Cell::Alive).unwrap();
+        grid.next_generation(&Rule::CONWAY, Topology::Bounded, &Neighborhood::Moore);
+        assert_eq!(Ok(Cell::Dead), grid.get(1, 1));
+        grid.update();
+        assert_eq!(Ok(Cell::Dead), grid.get(1, 1));
+    }
+    /// Tests dying cells keep their age until they are set to another state.
+    #[test]
+    fn dying_test() {
+        let mut grid = BitGrid::<WIDTH, HEIGHT>::default();
+        grid.set(2, 3, Cell::Dying(2)).unwrap();
+        assert_eq!(Ok(Cell::Dead), grid.get(2, 3));
+        grid.update();
+        assert_eq!(Ok(Cell::Dying(2)), grid.get(2, 3));
+        grid.set(4, 3, Cell::Alive).unwrap();
+        grid.update();
+        assert_eq!(Ok(Cell::Dying(2)), grid.get(2, 3));
+        grid.set(2, 3, Cell::Alive).unwrap();
+        grid.update();
+        assert_eq!(Ok(Cell::Alive), grid.get(2, 3));
+        grid.set(2, 3, Cell::Dead).unwrap();
+        grid.update();
+        assert_eq!(Ok(Cell::Dead), grid.get(2, 3));
+    }
+    /// Tests the bitwise generation is faster than counting the neighbors of each cell,
+    /// by enough of a margin to hold in unoptimized builds as well.
+    #[test]
+    fn bitwise_speed_test() {
+        const SIZE: usize = 256;
+        let mut grid = BitGrid::<SIZE, SIZE>::default();
+        let mut counted = GameOfLifeGrid::<SIZE, SIZE>::default();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if (x * 7 + y * 13) % 5 < 2 {
+                    grid.set(x, y, Cell::Alive).unwrap();
+                    counted.set(x, y, Cell::Alive).unwrap();
+                }
+            }
+        }
+        grid.update();
+        counted.update();
+        let time = |grid: &mut dyn FnMut()| {
+            let start = Instant::now();
+            for _ in 0..4 {
+                grid();
+            }
+            start.elapsed()
+        };
+        let bitwise = time(&mut || grid.next_generation(&Rule::CONWAY, Topology::Torus, &Neighborhood::Moore));
+        let each_cell = time(&mut || counted.next_generation(&Rule::CONWAY, Topology::Torus, &Neighborhood::Moore));
+        assert!(bitwise * 4 < each_cell);
+    }
+    proptest! {
+        /// Tests cells can be set and updated anywhere in a grid wider than a word.
+        #[test]
+        fn update_test(x in 0usize..150, y in 0usize..5) {
+            let mut grid = BitGrid::<150, 5>::default();
+            grid.set(x, y, Cell::Alive).unwrap();
+            assert_eq!(Ok(Cell::Dead), grid.get(x, y));
+            grid.update();
+            assert_eq!(Ok(Cell::Alive), grid.get(x, y));
+            assert_eq!(1, grid.cells.iter().map(|word| word.count_ones()).sum::<u32>());
+            grid.set(x, y, Cell::Dead).unwrap();
+            grid.update();
+            assert_eq!(Ok(Cell::Dead), grid.get(x, y));
+        }
+        /// Tests the bitwise generation matches counting the neighbors of each cell,
+        /// on a grid which spans multiple words with a partly used last word.
+        #[test]
+        fn next_generation_comparison_test(
+            cells in proptest::collection::vec((0usize..70, 0usize..6), 0..200),
+            rule in (0u16..1 << 9, 0u16..1 << 9).prop_map(|(birth, survival)| {
+                let counts = |mask: u16| (0..=8).filter(|count| mask & 1 << count != 0).collect::<Vec<_>>();
+                Rule::new(&counts(birth), &counts(survival)).unwrap()
+            }),
+            topology in prop_oneof![Just(Topology::Bounded), Just(Topology::Torus), Just(Topology::KleinBottle)],
+        ) {
+            let mut grid = BitGrid::<70, 6>::default();
+            let mut expected = DynGrid::new(70, 6).unwrap();
+            for (x, y) in cells {
+                grid.set(x, y, Cell::Alive).unwrap();
+                expected.set(x, y, Cell::Alive).unwrap();
+            }
+            grid.update();
+            expected.update();
+            for _ in 0..3 {
+                grid.next_generation(&rule, topology, &Neighborhood::Moore);
+                expected.next_generation(&rule, topology, &Neighborhood::Moore);
+                for y in 0..6 {
+                    for x in 0..70 {
+                        assert_eq!(expected.get(x, y), grid.get(x, y));
+                    }
+                }
+            }
+            for (i, word) in grid.cells.iter().enumerate() {
+                assert_eq!(0, word & !BitGrid::<70, 6>::word_mask(i % 2));
+            }
+        }
+        /// Tests rules with dying cells give the same generations
+        /// as a grid which stores every state of each cell.
+        #[test]
+        fn generations_comparison_test(
+            cells in proptest::collection::vec((0usize..70, 0usize..6), 0..200),
+            rule in prop_oneof![Just(Rule::BRIANS_BRAIN), Just(Rule::STAR_WARS)],
+            topology in prop_oneof![Just(Topology::Bounded), Just(Topology::Torus), Just(Topology::KleinBottle)],
+        ) {
+            let mut grid = BitGrid::<70, 6>::default();
+            let mut expected = DynGrid::new(70, 6).unwrap();
+            for (x, y) in cells {
+                grid.set(x, y, Cell::Alive).unwrap();
+                expected.set(x, y, Cell::Alive).unwrap();
+            }
+            grid.update();
+            expected.update();
+            for _ in 0..6 {
+                grid.next_generation(&rule, topology, &Neighborhood::Moore);
+                expected.next_generation(&rule, topology, &Neighborhood::Moore);
+                for y in 0..6 {
+                    for x in 0..70 {
+                        assert_eq!(expected.get(x, y), grid.get(x, y));
+                    }
+                }
+            }
+        }
+    }
+}
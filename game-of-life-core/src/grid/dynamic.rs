@@ -0,0 +1,141 @@
+use std::fmt;
+
+use super::{obj::CellHolder, Cell, CellOutOfBoundsError, CellState, Grid};
+
+/// A grid whose width and height are chosen at runtime.
+/// Cells are stored row by row in a single vector,
+/// so the cell at `(x, y)` is at index `y * width + x`.
+#[derive(Clone)]
+pub struct DynGrid<S: CellState = Cell> {
+    width: usize,
+    height: usize,
+    grid: Vec<CellHolder<S>>,
+}
+
+impl<S: CellState> DynGrid<S> {
+    /// Creates a grid of cells in the [quiescent](CellState::QUIESCENT) state,
+    /// dead for [`Cell`]s.
+    /// Returns Err if there are more cells than can be stored.
+    pub fn new(width: usize, height: usize) -> Result<Self, GridTooLargeError> {
+        let too_large = || GridTooLargeError::new(width, height);
+        let cells = width.checked_mul(height).ok_or_else(too_large)?;
+        let mut grid = Vec::new();
+        grid.try_reserve_exact(cells).map_err(|_| too_large())?;
+        grid.resize(cells, CellHolder::QUIESCENT);
+        Ok(DynGrid { width, height, grid })
+    }
+    fn index(&self, x: usize, y: usize) -> Result<usize, CellOutOfBoundsError> {
+        if x < self.width && y < self.height {
+            Ok(y * self.width + x)
+        } else {
+            Err(CellOutOfBoundsError::new(x, y))
+        }
+    }
+}
+
+impl<S: CellState> Grid<S> for DynGrid<S> {
+    fn get(&self, x: usize, y: usize) -> Result<S, CellOutOfBoundsError> {
+        let i = self.index(x, y)?;
+        Ok(self.grid[i].cell)
+    }
+    fn set(&mut self, x: usize, y: usize, cell: S) -> Result<(), CellOutOfBoundsError> {
+        let i = self.index(x, y)?;
+        self.grid[i].change_to = Some(cell);
+        Ok(())
+    }
+    fn update(&mut self) {
+        for cell in self.grid.iter_mut() {
+            if let Some(updated_cell) = cell.change_to.take() {
+                cell.cell = updated_cell;
+            }
+        }
+    }
+    fn width(&self) -> usize {
+        self.width
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct GridTooLargeError {
+    width: usize,
+    height: usize,
+}
+
+impl GridTooLargeError {
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        GridTooLargeError { width, height }
+    }
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+}
+impl std::error::Error for GridTooLargeError {}
+impl fmt::Display for GridTooLargeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a {}x{} grid has too many cells to store", self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn grid_new_test() {
+        let grid = DynGrid::new(5, 3).unwrap();
+        assert_eq!(5, grid.width());
+        assert_eq!(3, grid.height());
+        assert_eq!(15, grid.grid.len());
+        for y in 0..3 {
+            for x in 0..5 {
+                let r = grid.get(x, y);
+                assert!(r.is_ok());
+                assert_eq!(Cell::Dead, r.unwrap());
+            }
+        }
+    }
+    #[test]
+    fn empty_grid_test() {
+        let grid: DynGrid = DynGrid::new(0, 0).unwrap();
+        assert_eq!(CellOutOfBoundsError::new(0, 0), grid.get(0, 0).unwrap_err());
+    }
+    /// Tests sizes with more cells than fit in memory are refused rather than overflowing.
+    #[test]
+    fn too_large_test() {
+        let error = GridTooLargeError::new(usize::MAX, 2);
+        assert_eq!(Some(error), DynGrid::<Cell>::new(usize::MAX, 2).err());
+        assert_eq!(Some(GridTooLargeError::new(1 << 40, 1 << 30)), DynGrid::<Cell>::new(1 << 40, 1 << 30).err());
+        assert_eq!("a 3x4 grid has too many cells to store", GridTooLargeError::new(3, 4).to_string());
+        let grid: DynGrid = DynGrid::new(usize::MAX, 0).unwrap();
+        assert_eq!(usize::MAX, grid.width());
+        assert_eq!(CellOutOfBoundsError::new(0, 0), grid.get(0, 0).unwrap_err());
+    }
+    proptest! {
+        /// Tests every cell of a non-square grid can be set,
+        /// and only changes the cell at its own coordinates.
+        #[test]
+        fn set_in_bounds_test(width in 1usize..20, height in 1usize..20, x in 0usize..20, y in 0usize..20) {
+            let (x, y) = (x % width, y % height);
+            let mut grid = DynGrid::new(width, height).unwrap();
+            let r = grid.set(x, y, Cell::Alive);
+            assert!(r.is_ok());
+            assert_eq!(Cell::Dead, grid.get(x, y).unwrap());
+            grid.update();
+            assert_eq!(Cell::Alive, grid.get(x, y).unwrap());
+            assert_eq!(Cell::Alive, grid.grid[y * width + x].cell);
+            let alive = grid.grid.iter().filter(|holder| holder.cell == Cell::Alive).count();
+            assert_eq!(1, alive);
+        }
+        /// Tests coordinates past the width or height return an error.
+        #[test]
+        fn out_of_bounds_test(width in 0usize..20, height in 0usize..20, x in 0usize..40, y in 0usize..40) {
+            prop_assume!(x >= width || y >= height);
+            let mut grid = DynGrid::new(width, height).unwrap();
+            assert_eq!(CellOutOfBoundsError::new(x, y), grid.get(x, y).unwrap_err());
+            assert_eq!(CellOutOfBoundsError::new(x, y), grid.set(x, y, Cell::Alive).unwrap_err());
+        }
+    }
+}
@@ -1,38 +1,170 @@
+use crate::rule::Rule;
+
+use self::{neighborhood::Neighborhood, topology::Topology};
+
+pub mod bit;
+pub mod dynamic;
+pub mod neighborhood;
 pub mod obj;
+pub mod sparse;
+pub mod topology;
+
+/// Offsets of the eight neighbors around a cell.
+pub(crate) const NEIGHBOR_OFFSETS: [(isize, isize); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// Returns the coordinates each of the `offsets` away from `(x, y)`,
+/// leaving out those which would be below 0 or past `usize::MAX`,
+/// so no offset can overflow whatever coordinates a grid accepts.
+pub(crate) fn offset_positions(
+    x: usize,
+    y: usize,
+    offsets: impl IntoIterator<Item = (isize, isize)>,
+) -> impl Iterator<Item = (usize, usize)> {
+    offsets.into_iter().filter_map(move |offset| offset_position(x, y, offset))
+}
 
-pub trait Grid: Default {
+/// Returns the coordinates `(dx, dy)` away from `(x, y)`,
+/// or None if they would be below 0 or past `usize::MAX`.
+pub(crate) fn offset_position(x: usize, y: usize, (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+    Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+}
+
+/// The state of a single cell, which a [`Grid`] can hold.
+/// [`Cell`] is the state of Life-like games,
+/// other automata can define their own, such as [`Wire`](crate::wireworld::Wire).
+pub trait CellState: Copy + Eq + std::fmt::Debug {
+    /// The state every cell of a new grid starts in,
+    /// and the state of every cell outside of a bounded grid.
+    const QUIESCENT: Self;
+}
+
+impl CellState for Cell {
+    const QUIESCENT: Cell = Cell::Dead;
+}
+
+/// A grid of cells in the state `S`, which are [`Cell`]s unless stated otherwise.
+/// Counting neighbors and stepping under a [`Rule`] need a grid of [`Cell`]s,
+/// any other state is evolved with [`evolve_with`](crate::evolve_with).
+pub trait Grid<S: CellState = Cell> {
     /// Return Ok with a copy of the cell's value if in bounds,
     /// otherwise if cell was out of bounds, return Err.
-    fn get(&self, x: usize, y: usize) -> Result<Cell, CellOutOfBoundsError>;
+    fn get(&self, x: usize, y: usize) -> Result<S, CellOutOfBoundsError>;
     /// Return Ok if cell's state will be changed when calling [`update`](Grid::update),
     /// otherwise if cell was out of bounds, return Err.
-    fn set(&mut self, x: usize, y: usize, cell: Cell) -> Result<(), CellOutOfBoundsError>;
+    fn set(&mut self, x: usize, y: usize, cell: S) -> Result<(), CellOutOfBoundsError>;
     /// Updates all cell states based of those [`set`](Grid::set).
     fn update(&mut self);
     /// Returns the number of alive neighbors the cell has,
     /// if out of bounds, returns Err.
-    fn neighbor_count(&self, x: usize, y: usize) -> Result<usize, CellOutOfBoundsError> {
-        match self.get(x, y) {
-            Ok(_) => {
-                Ok([
-                    self.get(x, y + 1).ok(),
-                    self.get(x + 1, y + 1).ok(),
-                    self.get(x + 1, y).ok(),
-                    if y == 0 { None } else { self.get(x + 1, y - 1).ok() },
-                    if y == 0 { None } else { self.get(x, y - 1).ok() },
-                    if x == 0 || y == 0 { None } else { self.get(x - 1, y - 1).ok() },
-                    if x == 0 { None } else { self.get(x - 1, y).ok() },
-                    if x == 0 { None } else { self.get(x - 1, y + 1).ok() },
-                ]
-                .into_iter()
-                .filter(|r| matches!(r, Some(Cell::Alive)))
-                .count())
-            },
-            Err(e) => Err(e),
-        }
+    fn neighbor_count(&self, x: usize, y: usize) -> Result<usize, CellOutOfBoundsError>
+    where
+        Self: Grid<Cell>,
+    {
+        Grid::<Cell>::get(self, x, y)?;
+        Ok(offset_positions(x, y, NEIGHBOR_OFFSETS)
+            .filter(|&(nx, ny)| Grid::<Cell>::get(self, nx, ny) == Ok(Cell::Alive))
+            .count())
+    }
+    /// Changes every cell to its state in the next generation under `rule`,
+    /// with the edges of the grid joined by `topology`
+    /// and the neighbors of each cell chosen by `neighborhood`.
+    /// Grids which can calculate a generation faster than
+    /// counting the neighbors of each cell may override this.
+    fn next_generation(&mut self, rule: &Rule, topology: Topology, neighborhood: &Neighborhood)
+    where
+        Self: Sized + Grid<Cell>,
+    {
+        crate::evolve_each_cell(self, rule, topology, neighborhood);
     }
     fn width(&self) -> usize;
     fn height(&self) -> usize;
+    /// Returns the number of alive cells.
+    fn population(&self) -> usize
+    where
+        Self: Grid<Cell>,
+    {
+        let (width, height) = (Grid::<Cell>::width(self), Grid::<Cell>::height(self));
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| Grid::<Cell>::get(self, x, y) == Ok(Cell::Alive))
+            .count()
+    }
+}
+
+/// A grid without edges, where coordinates can be negative.
+/// Every cell is in bounds, so none of its functions return errors.
+pub trait UnboundedGrid {
+    /// Return a copy of the cell's value.
+    fn get(&self, x: i64, y: i64) -> Cell;
+    /// Sets the value the cell will be changed to when calling [`update`](UnboundedGrid::update).
+    fn set(&mut self, x: i64, y: i64, cell: Cell);
+    /// Updates all cell states based of those [`set`](UnboundedGrid::set).
+    fn update(&mut self);
+    /// Returns the coordinates of every alive cell, in no particular order.
+    fn alive_cells(&self) -> Vec<(i64, i64)>;
+    /// Returns the number of alive cells.
+    fn population(&self) -> usize {
+        self.alive_cells().len()
+    }
+    /// Returns the number of alive neighbors the cell has.
+    /// Coordinates past the range of `i64` are treated as dead.
+    fn neighbor_count(&self, x: i64, y: i64) -> usize {
+        NEIGHBOR_OFFSETS
+            .into_iter()
+            .filter_map(|(dx, dy)| Some((x.checked_add(dx as i64)?, y.checked_add(dy as i64)?)))
+            .filter(|&(nx, ny)| self.get(nx, ny) == Cell::Alive)
+            .count()
+    }
+    /// Returns the smallest rectangle containing every alive cell,
+    /// or None if there are no alive cells.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::around(self.alive_cells())
+    }
+}
+
+/// A rectangle of cells, where both corners are inclusive.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BoundingBox {
+    pub min_x: i64,
+    pub min_y: i64,
+    pub max_x: i64,
+    pub max_y: i64,
+}
+
+impl BoundingBox {
+    /// Returns the smallest rectangle containing all of the cells,
+    /// or None if there are no cells.
+    pub fn around(cells: impl IntoIterator<Item = (i64, i64)>) -> Option<Self> {
+        cells.into_iter().fold(None, |bounds, (x, y)| {
+            Some(match bounds {
+                None => BoundingBox { min_x: x, min_y: y, max_x: x, max_y: y },
+                Some(b) => BoundingBox {
+                    min_x: b.min_x.min(x),
+                    min_y: b.min_y.min(y),
+                    max_x: b.max_x.max(x),
+                    max_y: b.max_y.max(y),
+                },
+            })
+        })
+    }
+    pub fn width(&self) -> u64 {
+        self.max_x.abs_diff(self.min_x).saturating_add(1)
+    }
+    pub fn height(&self) -> u64 {
+        self.max_y.abs_diff(self.min_y).saturating_add(1)
+    }
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
 }
 
 // No default,
@@ -41,10 +173,24 @@ pub trait Grid: Default {
 // each of its cells' states to be.
 // Tests are written to assure the initial
 // state of such grids matches what is desired.
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
 pub enum Cell {
     Dead,
     Alive,
+    /// A cell which stopped surviving under a rule with more than two states,
+    /// and has been dying for this many generations.
+    /// It is not counted as an alive neighbor, and can not be born until it is dead.
+    Dying(u8),
+}
+
+impl Cell {
+    /// Returns the opposite state, alive if dead or dying, and dead if alive.
+    pub fn flipped(self) -> Cell {
+        match self {
+            Cell::Dead | Cell::Dying(_) => Cell::Alive,
+            Cell::Alive => Cell::Dead,
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -293,6 +439,7 @@ mod tests {
     proptest! {
         /// Tests the constructor of the `CellOutOfBoundsError` type.
         #[test]
+        #[allow(clippy::redundant_field_names)]
         fn new_cell_of_of_bounds_error_test(x in 0usize..usize::MAX, y in 0usize..usize::MAX) {
             let e = CellOutOfBoundsError::new(x, y);
             assert_eq!(CellOutOfBoundsError { x: x, y: y }, e);
@@ -305,76 +452,206 @@ mod tests {
             let e = CellOutOfBoundsError::new(x, y);
             assert_eq!(format!("cell ({}, {}) is out of bounds of the grid", x, y), e.to_string());
         }
-        /// Tests if retrieving a cell within the bounds of the grid works.
-        #[test]
-        fn get_in_bound_check_random_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
-            let grid = TestGrid::default();
-            let activated = grid.get(x, y);
-            assert!(activated.is_ok());
-            assert_eq!(Cell::Dead, activated.unwrap());
-        }
-        /// Tests if retrieving a cell outside of the bounds of the grid returns an error.
+        /// Tests the offsets around a cell at either end of `usize`
+        /// leave out exactly the positions below 0 or past `usize::MAX`.
         #[test]
-        fn get_out_bound_check_random_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
-            let grid = TestGrid::default();
-            let activated = grid.get(x, y);
-            assert!(activated.is_err());
-            assert_eq!(CellOutOfBoundsError::new(x, y), activated.unwrap_err());
+        fn offset_positions_test(x in edge(), y in edge()) {
+            let columns = 1 + usize::from(x > 0) + usize::from(x < usize::MAX);
+            let rows = 1 + usize::from(y > 0) + usize::from(y < usize::MAX);
+            let positions: Vec<_> = offset_positions(x, y, NEIGHBOR_OFFSETS).collect();
+            assert_eq!(columns * rows - 1, positions.len());
+            for (nx, ny) in positions {
+                assert!(nx.abs_diff(x) <= 1 && ny.abs_diff(y) <= 1);
+                assert_ne!((x, y), (nx, ny));
+            }
         }
-        /// Tests if activating a cell within the bounds of the grid works.
+        /// Tests counting the neighbors of a cell at either end of `usize`
+        /// on a grid accepting every coordinate never overflows,
+        /// and counts every alive neighbor which exists.
         #[test]
-        fn activate_in_bound_check_random_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
-            set_in_bound_check_random_test(x, y, Cell::Alive)
-        }
-        /// Tests if deactivating a cell within the bounds of the grid works.
-        #[test]
-        fn deactivate_in_bound_check_random_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
-            set_in_bound_check_random_test(x, y, Cell::Dead)
-        }
-        /// Tests if activating a cell outside of the bounds of the grid returns an error.
-        #[test]
-        fn activate_out_bound_check_random_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
-            set_out_bound_check_random_test(x, y, Cell::Alive)
-        }
-        /// Tests if deactivating a cell outside of the bounds of the grid returns an error.
-        #[test]
-        fn deactivate_out_bound_check_random_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
-            set_out_bound_check_random_test(x, y, Cell::Dead)
+        fn neighbor_count_at_usize_max_test(x in edge(), y in edge(), alive in prop::collection::vec(any::<bool>(), 8)) {
+            let mut grid = FarGrid::default();
+            let mut expected = 0;
+            for ((dx, dy), alive) in NEIGHBOR_OFFSETS.into_iter().zip(alive) {
+                if let (Some(nx), Some(ny), true) = (x.checked_add_signed(dx), y.checked_add_signed(dy), alive) {
+                    grid.set(nx, ny, Cell::Alive).unwrap();
+                    expected += 1;
+                }
+            }
+            // The cell itself, not its neighbor.
+            grid.set(x, y, Cell::Alive).unwrap();
+            grid.update();
+            assert_eq!(Ok(expected), grid.neighbor_count(x, y));
+            assert_eq!(Ok(expected), Topology::Bounded.neighbor_count(&grid, &Neighborhood::Moore, x, y));
         }
-        /// Tests if cell changes only after calling [`update`](Grid::update).
+        /// Tests other neighborhoods of a cell at either end of `usize`
+        /// on a bounded grid accepting every coordinate see the same neighbors as the Moore neighborhood does,
+        /// leaving out only those past either end.
         #[test]
-        fn update_test(x in 0..WIDTH, y in 0..HEIGHT) {
-            let mut grid = TestGrid::default();
-            let r = grid.set(x, y, Cell::Dead);
-            assert!(r.is_ok());
+        fn neighborhood_at_usize_max_test(
+            x in edge(),
+            y in edge(),
+            neighborhood in prop_oneof![
+                Just(Neighborhood::VonNeumann),
+                Just(Neighborhood::ExtendedVonNeumann(2)),
+                Just(Neighborhood::Custom(vec![(0, 0), (2, -1), (-2, 1), (1, 2)])),
+            ],
+            alive in prop::collection::vec(any::<bool>(), 12),
+        ) {
+            let offsets = neighborhood.offsets();
+            let mut grid = FarGrid::default();
+            let mut expected = Vec::new();
+            for (&(dx, dy), alive) in offsets.iter().zip(alive) {
+                let cell = if alive { Cell::Alive } else { Cell::Dead };
+                match offset_position(x, y, (dx, dy)) {
+                    Some((nx, ny)) => {
+                        grid.set(nx, ny, cell).unwrap();
+                        expected.push(cell);
+                    },
+                    None => expected.push(Cell::Dead),
+                }
+            }
             grid.update();
-            let cell = grid.get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Dead, cell.unwrap());
-            let r = grid.set(x, y, Cell::Alive);
-            assert!(r.is_ok());
-            let cell = grid.get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Dead, cell.unwrap());
-            grid.update();
-            let cell = grid.get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Alive, cell.unwrap());
+            let count = expected.iter().filter(|&&cell| cell == Cell::Alive).count();
+            assert_eq!(Ok(count), Topology::Bounded.neighbor_count(&grid, &neighborhood, x, y));
+            assert_eq!(Ok(expected), Topology::Bounded.neighbors(&grid, &neighborhood, x, y));
         }
     }
+    sized_tests! { mod sized {
+        proptest! {
+            /// Tests if retrieving a cell within the bounds of the grid works.
+            #[test]
+            fn get_in_bound_check_random_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
+                let grid = TestGrid::default();
+                let activated = grid.get(x, y);
+                assert!(activated.is_ok());
+                assert_eq!(Cell::Dead, activated.unwrap());
+            }
+            /// Tests if retrieving a cell outside of the bounds of the grid returns an error.
+            #[test]
+            fn get_out_bound_check_random_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
+                let grid = TestGrid::default();
+                let activated = grid.get(x, y);
+                assert!(activated.is_err());
+                assert_eq!(CellOutOfBoundsError::new(x, y), activated.unwrap_err());
+            }
+            /// Tests if retrieving a cell past only one side of the grid returns an error,
+            /// so the width and the height are never mixed up.
+            #[test]
+            fn get_out_bound_one_side_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX, in_x in 0..WIDTH, in_y in 0..HEIGHT) {
+                let grid = TestGrid::default();
+                assert_eq!(Err(CellOutOfBoundsError::new(x, in_y)), grid.get(x, in_y));
+                assert_eq!(Err(CellOutOfBoundsError::new(in_x, y)), grid.get(in_x, y));
+            }
+            /// Tests if activating a cell past only one side of the grid returns an error.
+            #[test]
+            fn activate_out_bound_one_side_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX, in_x in 0..WIDTH, in_y in 0..HEIGHT) {
+                set_out_bound_check_random_test(TestGrid::default(), x, in_y, Cell::Alive);
+                set_out_bound_check_random_test(TestGrid::default(), in_x, y, Cell::Alive);
+            }
+            /// Tests if activating a cell within the bounds of the grid works.
+            #[test]
+            fn activate_in_bound_check_random_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
+                set_in_bound_check_random_test(TestGrid::default(), x, y, Cell::Alive)
+            }
+            /// Tests if deactivating a cell within the bounds of the grid works.
+            #[test]
+            fn deactivate_in_bound_check_random_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
+                set_in_bound_check_random_test(TestGrid::default(), x, y, Cell::Dead)
+            }
+            /// Tests if activating a cell outside of the bounds of the grid returns an error.
+            #[test]
+            fn activate_out_bound_check_random_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
+                set_out_bound_check_random_test(TestGrid::default(), x, y, Cell::Alive)
+            }
+            /// Tests if deactivating a cell outside of the bounds of the grid returns an error.
+            #[test]
+            fn deactivate_out_bound_check_random_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
+                set_out_bound_check_random_test(TestGrid::default(), x, y, Cell::Dead)
+            }
+            /// Tests if cell changes only after calling [`update`](Grid::update).
+            #[test]
+            fn update_test(x in 0..WIDTH, y in 0..HEIGHT) {
+                let mut grid = TestGrid::default();
+                let r = grid.set(x, y, Cell::Dead);
+                assert!(r.is_ok());
+                grid.update();
+                let cell = grid.get(x, y);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Dead, cell.unwrap());
+                let r = grid.set(x, y, Cell::Alive);
+                assert!(r.is_ok());
+                let cell = grid.get(x, y);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Dead, cell.unwrap());
+                grid.update();
+                let cell = grid.get(x, y);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Alive, cell.unwrap());
+            }
+            /// Tests the population counts each distinct alive cell once.
+            #[test]
+            fn population_test(cells in proptest::collection::vec((0..WIDTH, 0..HEIGHT), 0..50)) {
+                let mut grid = TestGrid::default();
+                assert_eq!(0, grid.population());
+                for &(x, y) in &cells {
+                    grid.set(x, y, Cell::Alive).unwrap();
+                }
+                grid.update();
+                let distinct: std::collections::HashSet<_> = cells.into_iter().collect();
+                assert_eq!(distinct.len(), grid.population());
+            }
+        }
+    }}
 
 
     // Helper Functions
     // These are called in tests above.
 
-    fn set_in_bound_check_random_test(x: usize, y: usize, cell: Cell) {
-        let mut grid = TestGrid::default();
+    /// A grid accepting every coordinate up to `usize::MAX`,
+    /// which only stores its alive cells.
+    #[derive(Default)]
+    struct FarGrid {
+        alive: std::collections::HashSet<(usize, usize)>,
+        changes: Vec<((usize, usize), Cell)>,
+    }
+
+    impl Grid for FarGrid {
+        fn get(&self, x: usize, y: usize) -> Result<Cell, CellOutOfBoundsError> {
+            Ok(if self.alive.contains(&(x, y)) { Cell::Alive } else { Cell::Dead })
+        }
+        fn set(&mut self, x: usize, y: usize, cell: Cell) -> Result<(), CellOutOfBoundsError> {
+            self.changes.push(((x, y), cell));
+            Ok(())
+        }
+        fn update(&mut self) {
+            for (position, cell) in self.changes.drain(..) {
+                if cell == Cell::Alive {
+                    self.alive.insert(position);
+                } else {
+                    self.alive.remove(&position);
+                }
+            }
+        }
+        fn width(&self) -> usize {
+            usize::MAX
+        }
+        fn height(&self) -> usize {
+            usize::MAX
+        }
+    }
+
+    /// Returns coordinates at either end of `usize`.
+    fn edge() -> impl Strategy<Value = usize> {
+        prop_oneof![0usize..3, usize::MAX - 2..=usize::MAX]
+    }
+
+    fn set_in_bound_check_random_test(mut grid: impl Grid, x: usize, y: usize, cell: Cell) {
         let activated = grid.set(x, y, cell);
         assert!(activated.is_ok());
         assert_eq!((), activated.unwrap());
     }
-    fn set_out_bound_check_random_test(x: usize, y: usize, cell: Cell) {
-        let mut grid = TestGrid::default();
+    fn set_out_bound_check_random_test(mut grid: impl Grid, x: usize, y: usize, cell: Cell) {
         let activated = grid.set(x, y, cell);
         assert!(activated.is_err());
         assert_eq!(CellOutOfBoundsError::new(x, y), activated.unwrap_err());
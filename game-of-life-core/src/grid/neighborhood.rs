@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+
+use super::NEIGHBOR_OFFSETS;
+
+/// Which cells around a cell count as its neighbors.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub enum Neighborhood {
+    /// The eight cells touching the cell, including diagonally.
+    #[default]
+    Moore,
+    /// The four cells above, below, left and right of the cell.
+    VonNeumann,
+    /// Six neighbors, for a hexagonal grid stored on a square grid skewed so that
+    /// each row sits half a cell to the right of the row below it.
+    /// This is the Moore neighborhood without the cells above-right and below-left.
+    Hexagonal,
+    /// Every cell within this many cells of the cell on both axes,
+    /// so a range of 1 is the Moore neighborhood.
+    ExtendedMoore(usize),
+    /// Every cell within this many steps of the cell, moving only up, down, left or right,
+    /// a diamond around the cell, so a range of 1 is the von Neumann neighborhood.
+    ExtendedVonNeumann(usize),
+    /// Any cells, given as `(dx, dy)` offsets from the cell.
+    /// An offset of `(0, 0)` counts the cell as its own neighbor.
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighborhood {
+    /// Returns the offsets of the neighbors from the cell.
+    /// Moore neighbors are in the order of [`Configuration::of`](crate::rule::hensel::Configuration::of),
+    /// and von Neumann and hexagonal neighbors in the same order without the missing ones.
+    pub fn offsets(&self) -> Cow<'_, [(isize, isize)]> {
+        match self {
+            Neighborhood::Moore => Cow::Borrowed(&NEIGHBOR_OFFSETS),
+            Neighborhood::VonNeumann => {
+                Cow::Owned(NEIGHBOR_OFFSETS.into_iter().filter(|&(dx, dy)| dx == 0 || dy == 0).collect())
+            },
+            Neighborhood::Hexagonal => {
+                Cow::Owned(NEIGHBOR_OFFSETS.into_iter().filter(|&(dx, dy)| dx * dy != -1).collect())
+            },
+            Neighborhood::ExtendedMoore(range) => Cow::Owned(square(*range).collect()),
+            Neighborhood::ExtendedVonNeumann(range) => Cow::Owned(
+                square(*range).filter(|&(dx, dy)| dx.unsigned_abs() + dy.unsigned_abs() <= *range).collect(),
+            ),
+            Neighborhood::Custom(offsets) => Cow::Borrowed(offsets),
+        }
+    }
+    /// Returns true if the neighbors are exactly the eight cells of the Moore neighborhood,
+    /// in any order, such as an extended Moore neighborhood of range 1.
+    pub fn is_moore(&self) -> bool {
+        match self {
+            Neighborhood::Moore => true,
+            Neighborhood::ExtendedMoore(range) => *range == 1,
+            Neighborhood::VonNeumann | Neighborhood::Hexagonal | Neighborhood::ExtendedVonNeumann(_) => false,
+            Neighborhood::Custom(offsets) => {
+                offsets.len() == NEIGHBOR_OFFSETS.len() && NEIGHBOR_OFFSETS.iter().all(|offset| offsets.contains(offset))
+            },
+        }
+    }
+    /// Returns the farthest any neighbor is from the cell on either axis.
+    pub fn range(&self) -> usize {
+        match self {
+            Neighborhood::Moore | Neighborhood::VonNeumann | Neighborhood::Hexagonal => 1,
+            Neighborhood::ExtendedMoore(range) | Neighborhood::ExtendedVonNeumann(range) => *range,
+            Neighborhood::Custom(offsets) => {
+                offsets.iter().map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs())).max().unwrap_or(0)
+            },
+        }
+    }
+}
+
+/// Returns the offsets of every cell within `range` of the cell on both axes
+/// but the cell itself, row by row.
+fn square(range: usize) -> impl Iterator<Item = (isize, isize)> {
+    // Ranges past `isize::MAX` could never fit on a grid anyway.
+    let range = isize::try_from(range).unwrap_or(isize::MAX);
+    (-range..=range)
+        .flat_map(move |dy| (-range..=range).map(move |dx| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn default_neighborhood_test() {
+        assert_eq!(Neighborhood::Moore, Neighborhood::default());
+    }
+    #[test]
+    fn is_moore_test() {
+        assert!(Neighborhood::Moore.is_moore());
+        assert!(Neighborhood::ExtendedMoore(1).is_moore());
+        assert!(!Neighborhood::ExtendedMoore(2).is_moore());
+        assert!(!Neighborhood::Hexagonal.is_moore());
+        assert!(!Neighborhood::ExtendedVonNeumann(2).is_moore());
+        let mut reversed = NEIGHBOR_OFFSETS.to_vec();
+        reversed.reverse();
+        assert!(Neighborhood::Custom(reversed.clone()).is_moore());
+        reversed[0] = (0, 0);
+        assert!(!Neighborhood::Custom(reversed).is_moore());
+        assert!(!Neighborhood::Custom(vec![(1, 1); 8]).is_moore());
+    }
+    #[test]
+    fn offsets_test() {
+        assert_eq!(&NEIGHBOR_OFFSETS[..], &Neighborhood::Moore.offsets()[..]);
+        assert_eq!(vec![(0, 1), (1, 0), (0, -1), (-1, 0)], Neighborhood::VonNeumann.offsets().to_vec());
+        assert_eq!(
+            vec![(0, 1), (1, 1), (1, 0), (0, -1), (-1, -1), (-1, 0)],
+            Neighborhood::Hexagonal.offsets().to_vec(),
+        );
+        assert!(Neighborhood::ExtendedMoore(0).offsets().is_empty());
+        assert!(Neighborhood::ExtendedVonNeumann(0).offsets().is_empty());
+        let custom = Neighborhood::Custom(vec![(0, 0), (2, -3)]);
+        assert_eq!(vec![(0, 0), (2, -3)], custom.offsets().to_vec());
+        assert_eq!(3, custom.range());
+        assert_eq!(0, Neighborhood::Custom(vec![]).range());
+    }
+    proptest! {
+        /// Tests the extended Moore neighborhood has every cell of the square around the cell
+        /// but the cell itself, and a range of 1 has the same cells as the Moore neighborhood.
+        #[test]
+        fn extended_moore_test(range in 0usize..10) {
+            let neighborhood = Neighborhood::ExtendedMoore(range);
+            let offsets = neighborhood.offsets();
+            let side = 2 * range + 1;
+            assert_eq!(side * side - 1, offsets.len());
+            assert!(!offsets.contains(&(0, 0)));
+            assert!(offsets.iter().all(|&(dx, dy)| dx.unsigned_abs() <= range && dy.unsigned_abs() <= range));
+            assert_eq!(range, neighborhood.range());
+            if range == 1 {
+                let mut moore = NEIGHBOR_OFFSETS.to_vec();
+                moore.sort();
+                let mut offsets = offsets.to_vec();
+                offsets.sort();
+                assert_eq!(moore, offsets);
+            }
+        }
+        /// Tests the extended von Neumann neighborhood is the diamond of cells within
+        /// `range` steps of the cell, and a range of 1 has the same cells as the von Neumann neighborhood.
+        #[test]
+        fn extended_von_neumann_test(range in 0usize..10) {
+            let neighborhood = Neighborhood::ExtendedVonNeumann(range);
+            let offsets = neighborhood.offsets();
+            assert_eq!(2 * range * (range + 1), offsets.len());
+            assert!(!offsets.contains(&(0, 0)));
+            assert!(offsets.iter().all(|&(dx, dy)| dx.unsigned_abs() + dy.unsigned_abs() <= range));
+            assert_eq!(range, neighborhood.range());
+            if range == 1 {
+                let mut von_neumann = Neighborhood::VonNeumann.offsets().to_vec();
+                von_neumann.sort();
+                let mut offsets = offsets.to_vec();
+                offsets.sort();
+                assert_eq!(von_neumann, offsets);
+            }
+        }
+    }
+}
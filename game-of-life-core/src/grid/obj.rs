@@ -1,24 +1,30 @@
-use super::{Cell, CellOutOfBoundsError, Grid};
+use super::{Cell, CellOutOfBoundsError, CellState, Grid};
 
-pub struct GameOfLifeGrid<const WIDTH: usize, const HEIGHT: usize> {
-    grid: [[CellHolder; HEIGHT]; WIDTH],
+#[derive(Clone)]
+pub struct GameOfLifeGrid<const WIDTH: usize, const HEIGHT: usize, S: CellState = Cell> {
+    grid: [[CellHolder<S>; WIDTH]; HEIGHT],
 }
 
 #[derive(Clone, Copy, Debug)]
-struct CellHolder {
-    cell: Cell,
-    change_to: Option<Cell>,
+pub(super) struct CellHolder<S: CellState = Cell> {
+    pub(super) cell: S,
+    pub(super) change_to: Option<S>,
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> Default for GameOfLifeGrid<WIDTH, HEIGHT> {
+impl<S: CellState> CellHolder<S> {
+    pub(super) const QUIESCENT: CellHolder<S> = CellHolder { cell: S::QUIESCENT, change_to: None };
+}
+
+/// Every cell starts in the [quiescent](CellState::QUIESCENT) state, dead for [`Cell`]s.
+impl<const WIDTH: usize, const HEIGHT: usize, S: CellState> Default for GameOfLifeGrid<WIDTH, HEIGHT, S> {
     fn default() -> Self {
         GameOfLifeGrid {
-            grid: [[CellHolder { cell: Cell::Dead, change_to: None }; HEIGHT]; WIDTH],
+            grid: [[CellHolder::QUIESCENT; WIDTH]; HEIGHT],
         }
     }
 }
-impl<const WIDTH: usize, const HEIGHT: usize> Grid for GameOfLifeGrid<WIDTH, HEIGHT> {
-    fn get(&self, x: usize, y: usize) -> Result<Cell, CellOutOfBoundsError> {
+impl<const WIDTH: usize, const HEIGHT: usize, S: CellState> Grid<S> for GameOfLifeGrid<WIDTH, HEIGHT, S> {
+    fn get(&self, x: usize, y: usize) -> Result<S, CellOutOfBoundsError> {
         if let Some(cells) = self.grid.get(y) {
             if let Some(cell) = cells.get(x) {
                 Ok(cell.cell)
@@ -29,7 +35,7 @@ impl<const WIDTH: usize, const HEIGHT: usize> Grid for GameOfLifeGrid<WIDTH, HEI
             Err(CellOutOfBoundsError::new(x, y))
         }
     }
-    fn set(&mut self, x: usize, y: usize, cell: Cell) -> Result<(), CellOutOfBoundsError> {
+    fn set(&mut self, x: usize, y: usize, cell: S) -> Result<(), CellOutOfBoundsError> {
         if let Some(cells) = self.grid.get_mut(y) {
             if let Some(cell_to_change) = cells.get_mut(x) {
                 cell_to_change.change_to = Some(cell);
@@ -62,36 +68,38 @@ mod tests {
     use proptest::prelude::*;
     use crate::tests::*;
 
-    #[test]
-    fn grid_default_test() {
-        let grid = TestGrid::default();
-        for x in 0..WIDTH {
-            for y in 0..HEIGHT {
-                let r = grid.get(x, y);
-                assert!(r.is_ok());
-                let r = r.unwrap();
-                assert_eq!(Cell::Dead, r);
-                let direct_r = grid.grid[y][x].cell;
-                assert_eq!(direct_r, r);
+    sized_tests! { mod sized {
+        #[test]
+        fn grid_default_test() {
+            let grid = TestGrid::default();
+            for x in 0..WIDTH {
+                for y in 0..HEIGHT {
+                    let r = grid.get(x, y);
+                    assert!(r.is_ok());
+                    let r = r.unwrap();
+                    assert_eq!(Cell::Dead, r);
+                    let direct_r = grid.grid[y][x].cell;
+                    assert_eq!(direct_r, r);
+                }
             }
         }
-    }
-    #[test]
-    fn grid_size_test() {
-        let grid = TestGrid::default();
-        assert_eq!(WIDTH, grid.width());
-        assert_eq!(HEIGHT, grid.height());
-    }
-    proptest! {
         #[test]
-        fn activation_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
-            let mut grid = TestGrid::default();
-            let r = grid.grid[y][x].cell;
-            assert_eq!(Cell::Dead, r);
-            let r = grid.set(x, y, Cell::Alive);
-            assert!(r.is_ok());
-            let r = grid.grid[y][x].change_to.unwrap();
-            assert_eq!(Cell::Alive, r);
+        fn grid_size_test() {
+            let grid = TestGrid::default();
+            assert_eq!(WIDTH, grid.width());
+            assert_eq!(HEIGHT, grid.height());
         }
-    }
+        proptest! {
+            #[test]
+            fn activation_test(x in 0usize..WIDTH, y in 0usize..HEIGHT) {
+                let mut grid = TestGrid::default();
+                let r = grid.grid[y][x].cell;
+                assert_eq!(Cell::Dead, r);
+                let r = grid.set(x, y, Cell::Alive);
+                assert!(r.is_ok());
+                let r = grid.grid[y][x].change_to.unwrap();
+                assert_eq!(Cell::Alive, r);
+            }
+        }
+    }}
 }
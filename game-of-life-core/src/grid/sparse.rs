@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{grid::topology::Topology, rule::{hensel::Configuration, Rule}, Universe};
+
+use super::{neighborhood::Neighborhood, Cell, UnboundedGrid, NEIGHBOR_OFFSETS};
+
+/// An infinite grid which only stores its alive and dying cells,
+/// so it grows as patterns expand.
+#[derive(Clone, Default)]
+pub struct SparseGrid {
+    alive: HashSet<(i64, i64)>,
+    // How long each dying cell has been dying for.
+    dying: HashMap<(i64, i64), u8>,
+    change_to: HashMap<(i64, i64), Cell>,
+}
+
+impl UnboundedGrid for SparseGrid {
+    fn get(&self, x: i64, y: i64) -> Cell {
+        if self.alive.contains(&(x, y)) {
+            Cell::Alive
+        } else if let Some(&age) = self.dying.get(&(x, y)) {
+            Cell::Dying(age)
+        } else {
+            Cell::Dead
+        }
+    }
+    fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        self.change_to.insert((x, y), cell);
+    }
+    fn update(&mut self) {
+        for (position, cell) in self.change_to.drain() {
+            self.alive.remove(&position);
+            self.dying.remove(&position);
+            match cell {
+                Cell::Alive => {
+                    self.alive.insert(position);
+                },
+                Cell::Dying(age) => {
+                    self.dying.insert(position, age);
+                },
+                Cell::Dead => {},
+            }
+        }
+    }
+    fn alive_cells(&self) -> Vec<(i64, i64)> {
+        self.alive.iter().copied().collect()
+    }
+    fn population(&self) -> usize {
+        self.alive.len()
+    }
+}
+
+impl SparseGrid {
+    /// Evolves the grid by counting the alive neighbors of each cell in `neighborhood`,
+    /// for neighborhoods other than [Moore](Neighborhood::is_moore), which have no configurations.
+    fn evolve_counting(&mut self, rule: &Rule, neighborhood: &Neighborhood) {
+        let offsets = neighborhood.offsets();
+        let mut counts: HashMap<(i64, i64), usize> = self.dying.keys().map(|&position| (position, 0)).collect();
+        for &(x, y) in &self.alive {
+            counts.entry((x, y)).or_insert(0);
+            for &(dx, dy) in offsets.iter() {
+                // An alive cell is the neighbor of the cell it is offset from,
+                // which matters for neighborhoods which are not symmetric.
+                if let (Some(nx), Some(ny)) = (x.checked_sub(dx as i64), y.checked_sub(dy as i64)) {
+                    *counts.entry((nx, ny)).or_insert(0) += 1;
+                }
+            }
+        }
+        for ((x, y), count) in counts {
+            let cell = self.get(x, y);
+            let next = rule.next_cell(cell, count);
+            if next != cell {
+                self.set(x, y, next);
+            }
+        }
+        self.update();
+    }
+}
+
+impl Universe for SparseGrid {
+    /// Only alive cells and their neighbors can change,
+    /// so those are the only cells visited.
+    /// The grid has no edges, so the topology is ignored.
+    /// Rules which birth cells with no neighbors (`B0`) only
+    /// birth those next to alive cells, rather than the entire plane.
+    fn evolve(&mut self, rule: &Rule, _topology: Topology, neighborhood: &Neighborhood) {
+        if !neighborhood.is_moore() {
+            return self.evolve_counting(rule, neighborhood);
+        }
+        // Each cell's neighborhood has a bit set for each alive neighbor,
+        // as in `Configuration::of`, so rules can look at their arrangement.
+        // Dying cells change whether or not they have neighbors.
+        let mut neighborhoods: HashMap<(i64, i64), u8> = self.dying.keys().map(|&position| (position, 0)).collect();
+        for &(x, y) in &self.alive {
+            neighborhoods.entry((x, y)).or_insert(0);
+            for (i, (dx, dy)) in NEIGHBOR_OFFSETS.into_iter().enumerate() {
+                if let (Some(nx), Some(ny)) = (x.checked_add(dx as i64), y.checked_add(dy as i64)) {
+                    // The alive cell is on the opposite side of its neighbor,
+                    // which is four places further around.
+                    *neighborhoods.entry((nx, ny)).or_insert(0) |= 1 << ((i + 4) % NEIGHBOR_OFFSETS.len());
+                }
+            }
+        }
+        for ((x, y), neighborhood) in neighborhoods {
+            let cell = self.get(x, y);
+            let next = rule.next_cell_in(cell, Configuration::of(neighborhood));
+            if next != cell {
+                self.set(x, y, next);
+            }
+        }
+        self.update();
+    }
+    fn cells(&self) -> Vec<((i64, i64), Cell)> {
+        let alive = self.alive.iter().map(|&position| (position, Cell::Alive));
+        alive.chain(self.dying.iter().map(|(&position, &age)| (position, Cell::Dying(age)))).collect()
+    }
+    fn set_cells(&mut self, cells: &[((i64, i64), Cell)]) {
+        for &((x, y), cell) in cells {
+            self.set(x, y, cell);
+        }
+        self.update();
+    }
+    fn is_unbounded(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::grid::{dynamic::DynGrid, BoundingBox, Grid};
+
+    const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    #[test]
+    fn grid_default_test() {
+        let grid = SparseGrid::default();
+        assert_eq!(0, grid.population());
+        assert!(grid.alive_cells().is_empty());
+        assert_eq!(None, grid.bounding_box());
+        assert_eq!(Cell::Dead, grid.get(0, 0));
+    }
+    #[test]
+    fn neighbor_count_at_edge_of_range_test() {
+        let mut grid = SparseGrid::default();
+        grid.set(i64::MAX, i64::MAX - 1, Cell::Alive);
+        grid.set(i64::MIN, i64::MIN, Cell::Alive);
+        grid.update();
+        assert_eq!(1, grid.neighbor_count(i64::MAX, i64::MAX));
+        assert_eq!(0, grid.neighbor_count(i64::MIN, i64::MIN));
+        assert_eq!(1, grid.neighbor_count(i64::MIN + 1, i64::MIN + 1));
+    }
+    #[test]
+    fn bounding_box_test() {
+        let mut grid = SparseGrid::default();
+        grid.set(-3, 4, Cell::Alive);
+        grid.set(5, -2, Cell::Alive);
+        grid.set(0, 0, Cell::Alive);
+        grid.update();
+        let bounds = grid.bounding_box().unwrap();
+        assert_eq!(BoundingBox { min_x: -3, min_y: -2, max_x: 5, max_y: 4 }, bounds);
+        assert_eq!(9, bounds.width());
+        assert_eq!(7, bounds.height());
+        assert!(bounds.contains(-3, -2));
+        assert!(!bounds.contains(6, 0));
+    }
+    /// Tests a glider keeps travelling diagonally
+    /// for thousands of generations without edges to stop it.
+    #[test]
+    fn glider_travels_test() {
+        let mut grid = SparseGrid::default();
+        for (x, y) in GLIDER {
+            grid.set(x, y, Cell::Alive);
+        }
+        grid.update();
+        for _ in 0..4000 {
+            grid.evolve(&Rule::CONWAY, Topology::Bounded, &Neighborhood::Moore);
+        }
+        assert_eq!(5, grid.population());
+        for (x, y) in GLIDER {
+            assert_eq!(Cell::Alive, grid.get(x + 1000, y + 1000));
+        }
+        assert_eq!(Some(BoundingBox { min_x: 1000, min_y: 1000, max_x: 1002, max_y: 1002 }), grid.bounding_box());
+    }
+    proptest! {
+        /// Tests cells at any coordinates, including negative ones,
+        /// only change after calling [`update`](UnboundedGrid::update).
+        #[test]
+        fn update_test(x in any::<i64>(), y in any::<i64>()) {
+            let mut grid = SparseGrid::default();
+            grid.set(x, y, Cell::Alive);
+            assert_eq!(Cell::Dead, grid.get(x, y));
+            grid.update();
+            assert_eq!(Cell::Alive, grid.get(x, y));
+            assert_eq!(vec![(x, y)], grid.alive_cells());
+            grid.set(x, y, Cell::Dead);
+            assert_eq!(Cell::Alive, grid.get(x, y));
+            grid.update();
+            assert_eq!(Cell::Dead, grid.get(x, y));
+            assert_eq!(0, grid.population());
+        }
+        /// Tests the neighbor count of a cell surrounded by alive cells.
+        #[test]
+        fn neighbor_count_test(x in -1000i64..1000, y in -1000i64..1000) {
+            let mut grid = SparseGrid::default();
+            for (dx, dy) in NEIGHBOR_OFFSETS {
+                grid.set(x + dx as i64, y + dy as i64, Cell::Alive);
+            }
+            grid.set(x, y, Cell::Alive);
+            grid.update();
+            assert_eq!(8, grid.neighbor_count(x, y));
+            assert_eq!(0, grid.neighbor_count(x + 3, y));
+        }
+        /// Tests dying cells are stored, but are neither alive nor counted as neighbors.
+        #[test]
+        fn dying_test(x in -1000i64..1000, y in -1000i64..1000, age in 1u8..=u8::MAX) {
+            let mut grid = SparseGrid::default();
+            grid.set(x, y, Cell::Dying(age));
+            grid.update();
+            assert_eq!(Cell::Dying(age), grid.get(x, y));
+            assert_eq!(0, grid.population());
+            assert_eq!(0, grid.neighbor_count(x + 1, y));
+            assert_eq!(vec![((x, y), Cell::Dying(age))], grid.cells());
+            grid.set(x, y, Cell::Alive);
+            grid.update();
+            assert_eq!(vec![((x, y), Cell::Alive)], grid.cells());
+        }
+        /// Tests rules with dying states, or which depend on the arrangement of neighbors,
+        /// and other neighborhoods evolve the same as on a bounded grid, away from the edges.
+        #[test]
+        fn bounded_comparison_test(
+            cells in proptest::collection::vec((20usize..30, 20usize..30), 0..60),
+            rule in prop_oneof![Just(Rule::STAR_WARS), Just("B2-a/S12".parse().unwrap()), Just("B3/S2-i34q/C3".parse().unwrap())],
+            neighborhood in prop_oneof![
+                Just(Neighborhood::Moore),
+                Just(Neighborhood::VonNeumann),
+                Just(Neighborhood::Hexagonal),
+                Just(Neighborhood::Custom(vec![(0, 0), (1, 0), (2, -1)])),
+            ],
+        ) {
+            let mut grid = SparseGrid::default();
+            let mut expected = DynGrid::new(50, 50).unwrap();
+            for (x, y) in cells {
+                grid.set(x as i64, y as i64, Cell::Alive);
+                expected.set(x, y, Cell::Alive).unwrap();
+            }
+            grid.update();
+            expected.update();
+            for _ in 0..8 {
+                grid.evolve(&rule, Topology::Bounded, &neighborhood);
+                expected.evolve(&rule, Topology::Bounded, &neighborhood);
+            }
+            let mut cells = grid.cells();
+            cells.sort();
+            let mut expected = expected.cells();
+            expected.sort();
+            assert_eq!(expected, cells);
+        }
+    }
+}
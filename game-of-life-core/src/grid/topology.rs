@@ -0,0 +1,272 @@
+use super::{
+    neighborhood::Neighborhood, offset_position, offset_positions, Cell, CellOutOfBoundsError, CellState, Grid, NEIGHBOR_OFFSETS,
+};
+
+/// How the edges of a grid connect to each other
+/// when looking for the neighbors of a cell.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Topology {
+    /// Everything outside of the grid is dead.
+    #[default]
+    Bounded,
+    /// The left edge joins the right edge,
+    /// and the top edge joins the bottom edge.
+    Torus,
+    /// The left edge joins the right edge,
+    /// everything above the top or below the bottom is dead.
+    Cylinder,
+    /// The left edge joins the right edge,
+    /// and the top edge joins the bottom edge mirrored,
+    /// so crossing the top or bottom flips the column.
+    KleinBottle,
+    /// Both pairs of edges join mirrored,
+    /// crossing the left or right flips the row,
+    /// and crossing the top or bottom flips the column.
+    CrossSurface,
+}
+
+/// Where a coordinate ends up after moving along one axis.
+struct Wrapped {
+    coordinate: usize,
+    /// True if an odd number of edges were crossed to get there.
+    flipped: bool,
+    /// True if any edge was crossed to get there.
+    crossed: bool,
+}
+
+impl Wrapped {
+    fn new(coordinate: usize, delta: isize, size: usize) -> Self {
+        let moved = coordinate as i128 + delta as i128;
+        let size = size as i128;
+        let crossings = moved.div_euclid(size);
+        Wrapped {
+            coordinate: moved.rem_euclid(size) as usize,
+            flipped: crossings % 2 != 0,
+            crossed: crossings != 0,
+        }
+    }
+}
+
+impl Topology {
+    /// Returns the coordinates of the cell `(dx, dy)` away from `(x, y)`
+    /// on a grid of the given size, or None if that cell is off the grid.
+    /// `(x, y)` must be within the grid.
+    pub fn offset(&self, x: usize, y: usize, dx: isize, dy: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let column = Wrapped::new(x, dx, width);
+        let row = Wrapped::new(y, dy, height);
+        let mirror = |coordinate: usize, size: usize| size - 1 - coordinate;
+        match self {
+            Topology::Bounded => (!column.crossed && !row.crossed).then_some((column.coordinate, row.coordinate)),
+            Topology::Torus => Some((column.coordinate, row.coordinate)),
+            Topology::Cylinder => (!row.crossed).then_some((column.coordinate, row.coordinate)),
+            Topology::KleinBottle => {
+                let x = if row.flipped { mirror(column.coordinate, width) } else { column.coordinate };
+                Some((x, row.coordinate))
+            },
+            Topology::CrossSurface => {
+                let x = if row.flipped { mirror(column.coordinate, width) } else { column.coordinate };
+                let y = if column.flipped { mirror(row.coordinate, height) } else { row.coordinate };
+                Some((x, y))
+            },
+        }
+    }
+    /// Returns the number of alive neighbors the cell has in `neighborhood`
+    /// with the grid's edges joined by this topology,
+    /// if out of bounds, returns Err.
+    pub fn neighbor_count(
+        &self,
+        grid: &impl Grid,
+        neighborhood: &Neighborhood,
+        x: usize,
+        y: usize,
+    ) -> Result<usize, CellOutOfBoundsError> {
+        self.neighbor_count_at(grid, &neighborhood.offsets(), x, y)
+    }
+    /// Returns the number of alive neighbors the cell has at each of the `offsets`,
+    /// so the offsets of a neighborhood can be listed once for every cell of a generation.
+    pub(crate) fn neighbor_count_at(
+        &self,
+        grid: &impl Grid,
+        offsets: &[(isize, isize)],
+        x: usize,
+        y: usize,
+    ) -> Result<usize, CellOutOfBoundsError> {
+        if *self == Topology::Bounded && offsets == NEIGHBOR_OFFSETS {
+            // Let the grid count its own neighbors,
+            // in case it has a faster way of doing so.
+            return grid.neighbor_count(x, y);
+        }
+        grid.get(x, y)?;
+        if *self == Topology::Bounded {
+            // The grid decides where it ends, as when it counts its own neighbors.
+            return Ok(offset_positions(x, y, offsets.iter().copied())
+                .filter(|&(nx, ny)| matches!(grid.get(nx, ny), Ok(Cell::Alive)))
+                .count());
+        }
+        let (width, height) = (grid.width(), grid.height());
+        Ok(offsets
+            .iter()
+            .filter_map(|&(dx, dy)| self.offset(x, y, dx, dy, width, height))
+            .filter(|&(nx, ny)| matches!(grid.get(nx, ny), Ok(Cell::Alive)))
+            .count())
+    }
+    /// Returns the state of each neighbor of the cell in `neighborhood`,
+    /// in the order of its [offsets](Neighborhood::offsets),
+    /// with the grid's edges joined by this topology,
+    /// neighbors off the grid being [quiescent](CellState::QUIESCENT).
+    /// If out of bounds, returns Err.
+    pub fn neighbors<S: CellState>(
+        &self,
+        grid: &impl Grid<S>,
+        neighborhood: &Neighborhood,
+        x: usize,
+        y: usize,
+    ) -> Result<Vec<S>, CellOutOfBoundsError> {
+        self.neighbors_at(grid, &neighborhood.offsets(), x, y)
+    }
+    /// Returns the state of the neighbor at each of the `offsets`,
+    /// so the offsets of a neighborhood can be listed once for every cell of a generation.
+    pub(crate) fn neighbors_at<S: CellState>(
+        &self,
+        grid: &impl Grid<S>,
+        offsets: &[(isize, isize)],
+        x: usize,
+        y: usize,
+    ) -> Result<Vec<S>, CellOutOfBoundsError> {
+        grid.get(x, y)?;
+        let (width, height) = (grid.width(), grid.height());
+        Ok(offsets
+            .iter()
+            .map(|&(dx, dy)| {
+                let neighbor = match self {
+                    Topology::Bounded => offset_position(x, y, (dx, dy)),
+                    _ => self.offset(x, y, dx, dy, width, height),
+                };
+                neighbor
+                    .and_then(|(nx, ny)| grid.get(nx, ny).ok())
+                    .unwrap_or(S::QUIESCENT)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{grid::obj::GameOfLifeGrid, tests::*};
+
+    const ALL: [Topology; 5] = [
+        Topology::Bounded,
+        Topology::Torus,
+        Topology::Cylinder,
+        Topology::KleinBottle,
+        Topology::CrossSurface,
+    ];
+
+    #[test]
+    fn default_topology_test() {
+        assert_eq!(Topology::Bounded, Topology::default());
+    }
+    #[test]
+    fn offset_across_left_edge_test() {
+        assert_eq!(None, Topology::Bounded.offset(0, 2, -1, 0, 5, 4));
+        assert_eq!(Some((4, 2)), Topology::Torus.offset(0, 2, -1, 0, 5, 4));
+        assert_eq!(Some((4, 2)), Topology::Cylinder.offset(0, 2, -1, 0, 5, 4));
+        assert_eq!(Some((4, 2)), Topology::KleinBottle.offset(0, 2, -1, 0, 5, 4));
+        assert_eq!(Some((4, 1)), Topology::CrossSurface.offset(0, 2, -1, 0, 5, 4));
+    }
+    #[test]
+    fn offset_across_bottom_edge_test() {
+        assert_eq!(None, Topology::Bounded.offset(1, 3, 0, 1, 5, 4));
+        assert_eq!(Some((1, 0)), Topology::Torus.offset(1, 3, 0, 1, 5, 4));
+        assert_eq!(None, Topology::Cylinder.offset(1, 3, 0, 1, 5, 4));
+        assert_eq!(Some((3, 0)), Topology::KleinBottle.offset(1, 3, 0, 1, 5, 4));
+        assert_eq!(Some((3, 0)), Topology::CrossSurface.offset(1, 3, 0, 1, 5, 4));
+    }
+    #[test]
+    fn offset_across_corner_test() {
+        assert_eq!(None, Topology::Bounded.offset(4, 3, 1, 1, 5, 4));
+        assert_eq!(Some((0, 0)), Topology::Torus.offset(4, 3, 1, 1, 5, 4));
+        assert_eq!(None, Topology::Cylinder.offset(4, 3, 1, 1, 5, 4));
+        assert_eq!(Some((4, 0)), Topology::KleinBottle.offset(4, 3, 1, 1, 5, 4));
+        assert_eq!(Some((4, 3)), Topology::CrossSurface.offset(4, 3, 1, 1, 5, 4));
+    }
+    #[test]
+    fn torus_corner_neighbor_count_test() {
+        let mut grid = TestGrid::default();
+        grid.set(WIDTH - 1, HEIGHT - 1, Cell::Alive).unwrap();
+        grid.set(1, 0, Cell::Alive).unwrap();
+        grid.set(0, HEIGHT - 1, Cell::Alive).unwrap();
+        grid.update();
+        assert_eq!(Ok(1), Topology::Bounded.neighbor_count(&grid, &Neighborhood::Moore, 0, 0));
+        assert_eq!(Ok(3), Topology::Torus.neighbor_count(&grid, &Neighborhood::Moore, 0, 0));
+        assert_eq!(Ok(1), Topology::Cylinder.neighbor_count(&grid, &Neighborhood::Moore, 0, 0));
+        assert_eq!(Ok(2), Topology::Torus.neighbor_count(&grid, &Neighborhood::VonNeumann, 0, 0));
+        assert_eq!(Ok(3), Topology::Torus.neighbor_count(&grid, &Neighborhood::Hexagonal, 0, 0));
+        // The cell below-left is not a hexagonal neighbor.
+        grid.set(WIDTH - 1, 1, Cell::Alive).unwrap();
+        grid.update();
+        assert_eq!(Ok(3), Topology::Torus.neighbor_count(&grid, &Neighborhood::Hexagonal, 0, 0));
+        assert_eq!(Ok(4), Topology::Torus.neighbor_count(&grid, &Neighborhood::Moore, 0, 0));
+    }
+    /// Tests neighbors farther than the edges of the grid
+    /// wrap around a torus as many times as needed.
+    #[test]
+    fn extended_neighbors_test() {
+        let mut grid = GameOfLifeGrid::<3, 3>::default();
+        grid.set(1, 1, Cell::Alive).unwrap();
+        grid.update();
+        // The cell is 3 cells away from its own copies in each direction,
+        // so a range of 3 reaches 3 copies on each axis, besides the cell itself.
+        assert_eq!(Ok(8), Topology::Torus.neighbor_count(&grid, &Neighborhood::ExtendedMoore(3), 1, 1));
+        assert_eq!(Ok(0), Topology::Bounded.neighbor_count(&grid, &Neighborhood::ExtendedMoore(3), 1, 1));
+        assert_eq!(Ok(1), Topology::Bounded.neighbor_count(&grid, &Neighborhood::ExtendedMoore(3), 0, 0));
+        let neighbors = Topology::Bounded.neighbors(&grid, &Neighborhood::Custom(vec![(1, 1), (-5, 0), (0, 0)]), 0, 0);
+        assert_eq!(Ok(vec![Cell::Alive, Cell::Dead, Cell::Dead]), neighbors);
+    }
+    proptest! {
+        /// Tests moving within the grid is the same on every topology.
+        #[test]
+        fn offset_inside_test(x in 1..WIDTH-1, y in 1..HEIGHT-1, dx in -1isize..=1, dy in -1isize..=1) {
+            let expected = Some(((x as isize + dx) as usize, (y as isize + dy) as usize));
+            for topology in ALL {
+                assert_eq!(expected, topology.offset(x, y, dx, dy, WIDTH, HEIGHT));
+            }
+        }
+        /// Tests the bounded topology counts the same neighbors as the grid,
+        /// whether the grid counts them or not.
+        #[test]
+        fn bounded_neighbor_count_test(x in 0..WIDTH, y in 0..HEIGHT, alive in proptest::collection::vec((0..WIDTH, 0..HEIGHT), 0..20)) {
+            let mut grid = TestGrid::default();
+            for (ax, ay) in alive {
+                grid.set(ax, ay, Cell::Alive).unwrap();
+            }
+            grid.update();
+            assert_eq!(grid.neighbor_count(x, y), Topology::Bounded.neighbor_count(&grid, &Neighborhood::Moore, x, y));
+            // The same cells, counted without the grid's help.
+            assert_eq!(grid.neighbor_count(x, y), Topology::Bounded.neighbor_count(&grid, &Neighborhood::ExtendedMoore(1), x, y));
+        }
+        /// Tests every topology returns an error for cells outside of the grid.
+        #[test]
+        fn out_of_bounds_neighbor_count_test(x in WIDTH..usize::MAX, y in HEIGHT..usize::MAX) {
+            let grid = TestGrid::default();
+            for topology in ALL {
+                assert_eq!(CellOutOfBoundsError::new(x, y), topology.neighbor_count(&grid, &Neighborhood::Moore, x, y).unwrap_err());
+            }
+        }
+        /// Tests every cell on a torus has eight neighbors when the grid is full.
+        #[test]
+        fn full_torus_neighbor_count_test(x in 0..WIDTH, y in 0..HEIGHT) {
+            let mut grid = TestGrid::default();
+            for cy in 0..HEIGHT {
+                for cx in 0..WIDTH {
+                    grid.set(cx, cy, Cell::Alive).unwrap();
+                }
+            }
+            grid.update();
+            assert_eq!(Ok(8), Topology::Torus.neighbor_count(&grid, &Neighborhood::Moore, x, y));
+            assert_eq!(Ok(8), Topology::KleinBottle.neighbor_count(&grid, &Neighborhood::Moore, x, y));
+        }
+    }
+}
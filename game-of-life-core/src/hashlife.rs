@@ -0,0 +1,717 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    grid::{
+        neighborhood::Neighborhood, topology::Topology, BoundingBox, Cell, CellOutOfBoundsError, Grid, UnboundedGrid,
+        NEIGHBOR_OFFSETS,
+    },
+    rule::{hensel::Configuration, Rule},
+    Universe,
+};
+
+/// Index of a node within [`HashLife::nodes`].
+type NodeId = usize;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+/// The root can not grow past this level,
+/// where its center already covers the whole range of `i64`.
+const MAX_LEVEL: u32 = 65;
+
+/// A square of `2^level` by `2^level` cells.
+/// Level 0 nodes are single cells, every other level
+/// is made of four nodes of the level below.
+#[derive(Clone, Copy)]
+struct Node {
+    /// North west, north east, south west, and south east quadrants.
+    children: [NodeId; 4],
+    level: u32,
+    population: u64,
+}
+
+/// An infinite grid stored as a quadtree, where identical nodes are only stored once,
+/// and the future of each node is remembered once calculated.
+/// Patterns with repeating structure can be advanced by
+/// an exponential number of generations in a single call.
+///
+/// Cells are changed with [`set`](UnboundedGrid::set) and [`update`](UnboundedGrid::update)
+/// like any other grid, while [`advance`](HashLife::advance) and [`step`](HashLife::step)
+/// move the pattern forward through time.
+///
+/// Only alive and dead cells are stored, so rules with more than two states are not supported,
+/// and dying cells are set as dead.
+/// Each generation only looks one cell away,
+/// so neighborhoods reaching farther than that are not supported either.
+/// Both are refused with an [`UnsupportedRuleError`].
+#[derive(Clone)]
+pub struct HashLife {
+    rule: Rule,
+    neighborhood: Neighborhood,
+    nodes: Vec<Node>,
+    ids: HashMap<[NodeId; 4], NodeId>,
+    /// The center of a node, `2^j` generations later, keyed by the node and `j`.
+    results: HashMap<(NodeId, u32), NodeId>,
+    /// The node of each level which has no alive cells.
+    empty: Vec<NodeId>,
+    /// Centered on the origin, so it covers `-2^(level-1)..2^(level-1)` on both axes.
+    root: NodeId,
+    change_to: HashMap<(i64, i64), Cell>,
+}
+
+impl Default for HashLife {
+    fn default() -> Self {
+        // We know Conway's rule is supported
+        // in the Moore neighborhood.
+        HashLife::new(Rule::default()).unwrap()
+    }
+}
+
+impl HashLife {
+    /// Creates an empty universe which evolves under `rule`.
+    /// Returns Err if the rule births cells with no alive neighbors (`B0`),
+    /// as every cell of the infinite plane would be born,
+    /// or the rule has more than two states.
+    pub fn new(rule: Rule) -> Result<Self, UnsupportedRuleError> {
+        HashLife::check(&rule, &Neighborhood::default())?;
+        let leaf = |population| Node { children: [DEAD; 4], level: 0, population };
+        let mut hashlife = HashLife {
+            rule,
+            neighborhood: Neighborhood::default(),
+            nodes: vec![leaf(0), leaf(1)],
+            ids: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            change_to: HashMap::new(),
+        };
+        hashlife.root = hashlife.empty(3);
+        Ok(hashlife)
+    }
+    /// Creates a universe with the alive cells of `grid`,
+    /// where the cell at `(x, y)` of the grid is at `(x, y)` of the universe.
+    /// Returns Err if the rule is not supported, as in [`HashLife::new`].
+    pub fn from_grid(grid: &impl Grid, rule: Rule) -> Result<Self, UnsupportedRuleError> {
+        let mut hashlife = HashLife::new(rule)?;
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.get(x, y) == Ok(Cell::Alive) {
+                    hashlife.set(x as i64, y as i64, Cell::Alive);
+                }
+            }
+        }
+        hashlife.update();
+        Ok(hashlife)
+    }
+    /// Creates a universe with the alive cells of an unbounded grid.
+    /// Returns Err if the rule is not supported, as in [`HashLife::new`].
+    pub fn from_unbounded(grid: &impl UnboundedGrid, rule: Rule) -> Result<Self, UnsupportedRuleError> {
+        let mut hashlife = HashLife::new(rule)?;
+        for (x, y) in grid.alive_cells() {
+            hashlife.set(x, y, Cell::Alive);
+        }
+        hashlife.update();
+        Ok(hashlife)
+    }
+    /// Sets every cell of `grid` to the cell at the same coordinates of the universe,
+    /// then updates the grid.
+    /// Returns Err with the first alive cell found outside of the grid,
+    /// in which case the cells inside of the grid are still written.
+    pub fn write_to(&self, grid: &mut impl Grid) -> Result<(), CellOutOfBoundsError> {
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                grid.set(x, y, UnboundedGrid::get(self, x as i64, y as i64))?;
+            }
+        }
+        grid.update();
+        let outside = self
+            .alive_cells()
+            .into_iter()
+            .filter(|&(x, y)| x < 0 || y < 0 || x as u64 >= grid.width() as u64 || y as u64 >= grid.height() as u64)
+            .min_by_key(|&(x, y)| (y, x));
+        match outside {
+            // Negative coordinates can not be represented by the error,
+            // so they wrap around to the largest coordinates instead.
+            Some((x, y)) => Err(CellOutOfBoundsError::new(x as usize, y as usize)),
+            None => Ok(()),
+        }
+    }
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+    /// Changes the rule the universe evolves under,
+    /// forgetting every future calculated under the previous rule.
+    /// Returns Err without changing the rule if it is not supported, as in [`HashLife::new`].
+    pub fn set_rule(&mut self, rule: Rule) -> Result<(), UnsupportedRuleError> {
+        HashLife::check(&rule, &self.neighborhood)?;
+        if self.rule != rule {
+            self.rule = rule;
+            self.results.clear();
+        }
+        Ok(())
+    }
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighborhood
+    }
+    /// Changes the neighborhood the universe evolves in,
+    /// forgetting every future calculated in the previous neighborhood.
+    /// Returns Err without changing the neighborhood if it reaches farther than one cell away.
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) -> Result<(), UnsupportedRuleError> {
+        HashLife::check(&self.rule, &neighborhood)?;
+        if self.neighborhood != neighborhood {
+            self.neighborhood = neighborhood;
+            self.results.clear();
+        }
+        Ok(())
+    }
+    fn check(rule: &Rule, neighborhood: &Neighborhood) -> Result<(), UnsupportedRuleError> {
+        if rule.births(0) || rule.states() != 2 || neighborhood.range() > 1 {
+            return Err(UnsupportedRuleError::new(*rule, neighborhood.clone()));
+        }
+        Ok(())
+    }
+    /// Moves the universe forward by `2^log2_generations` generations.
+    ///
+    /// # Panics
+    /// If the pattern reaches the edge of the range of `i64`.
+    pub fn advance(&mut self, log2_generations: u32) {
+        // Grow the root until the pattern fits in its innermost sixteenth,
+        // cells travel at most one cell per generation,
+        // so the result (the center quarter) contains all of them.
+        while self.nodes[self.root].level < log2_generations + 3 || !self.is_padded() {
+            if self.nodes[self.root].level == MAX_LEVEL {
+                // The root can not grow any further, but the pattern may still have room
+                // to travel that far within the center, otherwise it has room for half of the generations.
+                if self.has_room(log2_generations) {
+                    break;
+                }
+                assert!(log2_generations > 0, "pattern grew past the range of i64");
+                self.advance(log2_generations - 1);
+                self.advance(log2_generations - 1);
+                return;
+            }
+            self.expand();
+        }
+        self.root = self.successor(self.root, log2_generations);
+    }
+    /// Moves the universe forward by any number of generations,
+    /// by advancing it by each power of two making up the number.
+    ///
+    /// # Panics
+    /// If the pattern reaches the edge of the range of `i64`.
+    pub fn step(&mut self, generations: u64) {
+        for bit in 0..u64::BITS {
+            if generations & 1 << bit != 0 {
+                self.advance(bit);
+            }
+        }
+    }
+    /// Returns the node made of the four quadrants, reusing an identical node if one exists.
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.ids.get(&children) {
+            return id;
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            children,
+            level: self.nodes[children[0]].level + 1,
+            population: children.iter().map(|&child| self.nodes[child].population).sum(),
+        });
+        self.ids.insert(children, id);
+        id
+    }
+    fn empty(&mut self, level: u32) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = self.empty[self.empty.len() - 1];
+            let id = self.join([below; 4]);
+            self.empty.push(id);
+        }
+        self.empty[level as usize]
+    }
+    /// Doubles the size of the root, keeping the pattern centered.
+    fn expand(&mut self) {
+        let level = self.nodes[self.root].level;
+        assert!(level < MAX_LEVEL, "pattern grew past the range of i64");
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.nodes[self.root].children;
+        let children = [
+            self.join([e, e, e, nw]),
+            self.join([e, e, ne, e]),
+            self.join([e, sw, e, e]),
+            self.join([se, e, e, e]),
+        ];
+        self.root = self.join(children);
+    }
+    /// Returns true if every alive cell of the root is within its innermost sixteenth.
+    fn is_padded(&mut self) -> bool {
+        let center = self.center(self.root);
+        let inner = self.center(center);
+        self.nodes[inner].population == self.nodes[self.root].population
+    }
+    /// Returns true if every alive cell of the root stays within its center
+    /// for `2^log2_generations` generations, even when traveling one cell per generation.
+    /// Looser than [`is_padded`](HashLife::is_padded), though slower as it looks at every alive cell.
+    fn has_room(&self, log2_generations: u32) -> bool {
+        let level = self.nodes[self.root].level;
+        if log2_generations + 3 > level {
+            return false;
+        }
+        let (half, margin) = (1i128 << (level - 2), 1i128 << log2_generations);
+        let within = |min: i64, max: i64| min as i128 - margin >= -half && max as i128 + margin < half;
+        match self.bounding_box() {
+            Some(bounds) => within(bounds.min_x, bounds.max_x) && within(bounds.min_y, bounds.max_y),
+            None => true,
+        }
+    }
+    /// Returns the node of half the size at the center of the node.
+    fn center(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.nodes[id].children;
+        let children = [
+            self.nodes[nw].children[3],
+            self.nodes[ne].children[2],
+            self.nodes[sw].children[1],
+            self.nodes[se].children[0],
+        ];
+        self.join(children)
+    }
+    /// Returns the center of the node `2^j` generations later,
+    /// `j` must be at most two less than the level of the node.
+    fn successor(&mut self, id: NodeId, j: u32) -> NodeId {
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+        let node = self.nodes[id];
+        let result = if node.population == 0 {
+            self.empty(node.level - 1)
+        } else if node.level == 2 {
+            self.evolve_leaves(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let [_, nw_ne, nw_sw, nw_se] = self.nodes[nw].children;
+            let [ne_nw, _, ne_sw, ne_se] = self.nodes[ne].children;
+            let [sw_nw, sw_ne, _, sw_se] = self.nodes[sw].children;
+            let [se_nw, se_ne, se_sw, _] = self.nodes[se].children;
+            // Nine overlapping squares, each half the size of the node.
+            let squares = [
+                nw,
+                self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+                ne,
+                self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+                self.join([nw_se, ne_sw, sw_ne, se_nw]),
+                self.join([ne_sw, ne_se, se_nw, se_ne]),
+                sw,
+                self.join([sw_ne, se_nw, sw_se, se_sw]),
+                se,
+            ];
+            // Going at full speed spends half of the time on each stage,
+            // otherwise the first stage only takes the center of each square.
+            let full_speed = j == node.level - 2;
+            let mut centers = [DEAD; 9];
+            for (center, square) in centers.iter_mut().zip(squares) {
+                *center = if full_speed { self.successor(square, j - 1) } else { self.center(square) };
+            }
+            let remaining = if full_speed { j - 1 } else { j };
+            let [c00, c01, c02, c10, c11, c12, c20, c21, c22] = centers;
+            let quadrants = [
+                self.join([c00, c01, c10, c11]),
+                self.join([c01, c02, c11, c12]),
+                self.join([c10, c11, c20, c21]),
+                self.join([c11, c12, c21, c22]),
+            ];
+            let mut children = [DEAD; 4];
+            for (child, quadrant) in children.iter_mut().zip(quadrants) {
+                *child = self.successor(quadrant, remaining);
+            }
+            self.join(children)
+        };
+        self.results.insert((id, j), result);
+        result
+    }
+    /// Returns the center 2x2 cells of a 4x4 node one generation later.
+    fn evolve_leaves(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (y, row) in cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = self.leaf(id, x as u128, y as u128) == ALIVE;
+            }
+        }
+        let offsets = self.neighborhood.offsets();
+        let moore = self.neighborhood.is_moore();
+        let mut children = [DEAD; 4];
+        for (i, child) in children.iter_mut().enumerate() {
+            let (x, y) = (1 + i % 2, 1 + i / 2);
+            let alive = |&(dx, dy): &(isize, isize)| cells[y.wrapping_add_signed(dy)][x.wrapping_add_signed(dx)];
+            let cell = if cells[y][x] { Cell::Alive } else { Cell::Dead };
+            let next = if moore {
+                let neighborhood = NEIGHBOR_OFFSETS
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, offset)| alive(offset))
+                    .fold(0, |neighborhood, (i, _)| neighborhood | 1 << i);
+                self.rule.next_cell_in(cell, Configuration::of(neighborhood))
+            } else {
+                self.rule.next_cell(cell, offsets.iter().filter(|offset| alive(offset)).count())
+            };
+            *child = match next {
+                Cell::Alive => ALIVE,
+                Cell::Dead | Cell::Dying(_) => DEAD,
+            };
+        }
+        self.join(children)
+    }
+    /// Returns the leaf at `(x, y)` relative to the north west corner of the node.
+    fn leaf(&self, mut id: NodeId, x: u128, y: u128) -> NodeId {
+        while self.nodes[id].level > 0 {
+            let half = 1 << (self.nodes[id].level - 1);
+            let quadrant = usize::from(x & half != 0) + 2 * usize::from(y & half != 0);
+            id = self.nodes[id].children[quadrant];
+        }
+        id
+    }
+    /// Returns the node with the leaf at `(x, y)`, relative to the north west corner, replaced.
+    fn with_leaf(&mut self, id: NodeId, x: u128, y: u128, leaf: NodeId) -> NodeId {
+        let node = self.nodes[id];
+        if node.level == 0 {
+            return leaf;
+        }
+        let half = 1 << (node.level - 1);
+        let quadrant = usize::from(x & half != 0) + 2 * usize::from(y & half != 0);
+        let mut children = node.children;
+        children[quadrant] = self.with_leaf(children[quadrant], x & !half, y & !half, leaf);
+        self.join(children)
+    }
+    /// Returns the coordinates of the north west corner of the root.
+    /// The corners of the largest roots are past the range of `i64`,
+    /// even though every alive cell within them is not.
+    fn origin(&self) -> i128 {
+        -(1i128 << (self.nodes[self.root].level - 1))
+    }
+    /// Returns `(x, y)` relative to the north west corner of the root,
+    /// or None if the root does not cover it.
+    fn relative(&self, x: i64, y: i64) -> Option<(u128, u128)> {
+        let size = 1i128 << self.nodes[self.root].level;
+        let x = x as i128 - self.origin();
+        let y = y as i128 - self.origin();
+        ((0..size).contains(&x) && (0..size).contains(&y)).then_some((x as u128, y as u128))
+    }
+    /// Returns the corner of the quadrant `i` of a node at `level`, whose own corner is at `(x, y)`.
+    fn quadrant_corner(level: u32, i: usize, x: i128, y: i128) -> (i128, i128) {
+        let half = 1i128 << (level - 1);
+        (x + half * (i % 2) as i128, y + half * (i / 2) as i128)
+    }
+    fn collect_alive(&self, id: NodeId, x: i128, y: i128, alive: &mut Vec<(i64, i64)>) {
+        let node = self.nodes[id];
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            alive.push((x as i64, y as i64));
+            return;
+        }
+        for (i, &child) in node.children.iter().enumerate() {
+            let (x, y) = HashLife::quadrant_corner(node.level, i, x, y);
+            self.collect_alive(child, x, y, alive);
+        }
+    }
+    /// Returns the bounds of the alive cells of the node, whose corner is at `(x, y)`.
+    fn bounds(&self, id: NodeId, x: i128, y: i128) -> Option<BoundingBox> {
+        let node = self.nodes[id];
+        if node.population == 0 {
+            return None;
+        }
+        if node.level == 0 {
+            let (x, y) = (x as i64, y as i64);
+            return Some(BoundingBox { min_x: x, min_y: y, max_x: x, max_y: y });
+        }
+        node.children
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &child)| {
+                let (x, y) = HashLife::quadrant_corner(node.level, i, x, y);
+                self.bounds(child, x, y)
+            })
+            .reduce(|a, b| BoundingBox {
+                min_x: a.min_x.min(b.min_x),
+                min_y: a.min_y.min(b.min_y),
+                max_x: a.max_x.max(b.max_x),
+                max_y: a.max_y.max(b.max_y),
+            })
+    }
+}
+
+impl UnboundedGrid for HashLife {
+    fn get(&self, x: i64, y: i64) -> Cell {
+        match self.relative(x, y) {
+            Some((x, y)) if self.leaf(self.root, x, y) == ALIVE => Cell::Alive,
+            _ => Cell::Dead,
+        }
+    }
+    fn set(&mut self, x: i64, y: i64, cell: Cell) {
+        self.change_to.insert((x, y), cell);
+    }
+    fn update(&mut self) {
+        let changes: Vec<_> = self.change_to.drain().collect();
+        for ((x, y), cell) in changes {
+            while self.relative(x, y).is_none() {
+                self.expand();
+            }
+            // We know the root covers the cell
+            // because we just expanded it until it did.
+            let (x, y) = self.relative(x, y).unwrap();
+            let leaf = match cell {
+                Cell::Alive => ALIVE,
+                Cell::Dead | Cell::Dying(_) => DEAD,
+            };
+            self.root = self.with_leaf(self.root, x, y, leaf);
+        }
+    }
+    fn alive_cells(&self) -> Vec<(i64, i64)> {
+        let mut alive = Vec::new();
+        self.collect_alive(self.root, self.origin(), self.origin(), &mut alive);
+        alive
+    }
+    fn population(&self) -> usize {
+        self.nodes[self.root].population as usize
+    }
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounds(self.root, self.origin(), self.origin())
+    }
+}
+
+impl Universe for HashLife {
+    /// Advances the universe by a single generation.
+    /// The universe has no edges, so the topology is ignored.
+    ///
+    /// # Panics
+    /// If the rule or neighborhood is not supported,
+    /// which [`GameOfLife`](crate::GameOfLife) refuses before evolving, see [`Universe::check_rule`].
+    fn evolve(&mut self, rule: &Rule, _topology: Topology, neighborhood: &Neighborhood) {
+        if let Err(e) = self.set_rule(*rule).and_then(|_| self.set_neighborhood(neighborhood.clone())) {
+            panic!("{e}");
+        }
+        self.advance(0);
+    }
+    fn check_rule(&self, rule: &Rule, neighborhood: &Neighborhood) -> Result<(), UnsupportedRuleError> {
+        HashLife::check(rule, neighborhood)
+    }
+    fn cells(&self) -> Vec<((i64, i64), Cell)> {
+        self.alive_cells().into_iter().map(|position| (position, Cell::Alive)).collect()
+    }
+    fn set_cells(&mut self, cells: &[((i64, i64), Cell)]) {
+        for &((x, y), cell) in cells {
+            self.set(x, y, cell);
+        }
+        self.update();
+    }
+    fn is_unbounded(&self) -> bool {
+        true
+    }
+}
+
+/// The error returned when [`HashLife`] is given a rule or neighborhood it can not evolve.
+#[derive(PartialEq, Eq, Debug)]
+pub struct UnsupportedRuleError {
+    rule: Rule,
+    neighborhood: Neighborhood,
+}
+
+impl UnsupportedRuleError {
+    pub(crate) fn new(rule: Rule, neighborhood: Neighborhood) -> Self {
+        UnsupportedRuleError { rule, neighborhood }
+    }
+    pub fn rule(&self) -> &Rule { &self.rule }
+    pub fn neighborhood(&self) -> &Neighborhood { &self.neighborhood }
+}
+impl std::error::Error for UnsupportedRuleError {}
+impl fmt::Display for UnsupportedRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.rule.births(0) {
+            write!(f, "HashLife does not support rules with B0, such as {}", self.rule)
+        } else if self.rule.states() != 2 {
+            write!(f, "HashLife does not support rules with {} states, such as {}", self.rule.states(), self.rule)
+        } else {
+            write!(f, "HashLife does not support neighborhoods with a range of {}", self.neighborhood.range())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{grid::sparse::SparseGrid, tests::*, GameOfLife};
+
+    const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    const R_PENTOMINO: [(i64, i64); 5] = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+    fn with_cells(cells: &[(i64, i64)]) -> HashLife {
+        let mut hashlife = HashLife::default();
+        for &(x, y) in cells {
+            hashlife.set(x, y, Cell::Alive);
+        }
+        hashlife.update();
+        hashlife
+    }
+
+    #[test]
+    fn empty_test() {
+        let hashlife = HashLife::default();
+        assert_eq!(0, hashlife.population());
+        assert_eq!(None, hashlife.bounding_box());
+        assert!(hashlife.alive_cells().is_empty());
+    }
+    #[test]
+    fn b0_rule_test() {
+        let rule = "B0/S".parse().unwrap();
+        let e = HashLife::new(rule).err();
+        assert_eq!(Some(UnsupportedRuleError::new(rule, Neighborhood::Moore)), e);
+        assert_eq!("HashLife does not support rules with B0, such as B0/S", e.unwrap().to_string());
+        assert!(GameOfLife::<HashLife>::new(rule).is_err());
+    }
+    #[test]
+    fn generations_rule_test() {
+        let mut hashlife = HashLife::default();
+        let e = UnsupportedRuleError::new(Rule::BRIANS_BRAIN, Neighborhood::Moore);
+        assert_eq!(Err(e), hashlife.set_rule(Rule::BRIANS_BRAIN));
+        assert_eq!(&Rule::CONWAY, hashlife.rule());
+        let mut game = GameOfLife::<HashLife>::default();
+        assert!(game.set_rule(Rule::BRIANS_BRAIN).is_err());
+        assert_eq!(&Rule::CONWAY, game.rule());
+    }
+    #[test]
+    fn extended_neighborhood_test() {
+        let mut hashlife = HashLife::default();
+        let e = UnsupportedRuleError::new(Rule::CONWAY, Neighborhood::ExtendedMoore(2));
+        assert_eq!("HashLife does not support neighborhoods with a range of 2", e.to_string());
+        assert_eq!(Err(e), hashlife.set_neighborhood(Neighborhood::ExtendedMoore(2)));
+        assert_eq!(&Neighborhood::Moore, hashlife.neighborhood());
+        let game = GameOfLife::<HashLife>::default().with_neighborhood(Neighborhood::ExtendedMoore(2));
+        assert!(game.is_err());
+    }
+    /// Tests a glider has moved a quarter of the generations diagonally,
+    /// after advancing by a million generations at once.
+    #[test]
+    fn glider_advance_test() {
+        let mut hashlife = with_cells(&GLIDER);
+        hashlife.advance(20);
+        assert_eq!(5, hashlife.population());
+        let moved = 1 << 18;
+        let mut expected: Vec<_> = GLIDER.iter().map(|&(x, y)| (x + moved, y + moved)).collect();
+        let mut alive = hashlife.alive_cells();
+        expected.sort();
+        alive.sort();
+        assert_eq!(expected, alive);
+        assert_eq!(Some(BoundingBox { min_x: moved, min_y: moved, max_x: moved + 2, max_y: moved + 2 }), hashlife.bounding_box());
+    }
+    /// Tests a glider can travel as far as the range of `i64` allows,
+    /// which is past the largest root whose corners are within it.
+    #[test]
+    fn glider_step_far_test() {
+        let mut hashlife = with_cells(&GLIDER);
+        hashlife.step(1 << 62);
+        let moved = 1 << 60;
+        let mut expected: Vec<_> = GLIDER.iter().map(|&(x, y)| (x + moved, y + moved)).collect();
+        let mut alive = hashlife.alive_cells();
+        expected.sort();
+        alive.sort();
+        assert_eq!(expected, alive);
+        // `u64::MAX` is three generations past a whole number of periods of four.
+        let mut hashlife = with_cells(&GLIDER);
+        hashlife.step(u64::MAX);
+        let mut phase = with_cells(&GLIDER);
+        phase.step(3);
+        let moved = (1 << 62) - 1;
+        let mut expected: Vec<_> = phase.alive_cells().iter().map(|&(x, y)| (x + moved, y + moved)).collect();
+        let mut alive = hashlife.alive_cells();
+        expected.sort();
+        alive.sort();
+        assert_eq!(expected, alive);
+    }
+    /// Tests the R-pentomino stabilizes to 116 cells, six of which are gliders.
+    #[test]
+    fn r_pentomino_test() {
+        let mut hashlife = with_cells(&R_PENTOMINO);
+        hashlife.step(1103);
+        assert_eq!(116, hashlife.population());
+        hashlife.advance(12);
+        assert_eq!(116, hashlife.population());
+    }
+    #[test]
+    fn grid_round_trip_test() {
+        let mut grid = TestGrid::default();
+        for (x, y) in GLIDER {
+            grid.set(x as usize + 4, y as usize + 4, Cell::Alive).unwrap();
+        }
+        grid.update();
+        let mut hashlife = HashLife::from_grid(&grid, Rule::CONWAY).unwrap();
+        assert_eq!(5, hashlife.population());
+        hashlife.step(4);
+        let mut result = TestGrid::default();
+        assert_eq!(Ok(()), hashlife.write_to(&mut result));
+        for (x, y) in GLIDER {
+            assert_eq!(Ok(Cell::Alive), result.get(x as usize + 5, y as usize + 5));
+        }
+        hashlife.step(4 * 8);
+        assert_eq!(Err(CellOutOfBoundsError::new(14, 13)), hashlife.write_to(&mut result));
+    }
+    #[test]
+    fn evolve_test() {
+        let mut game = GameOfLife::<HashLife>::new(Rule::HIGHLIFE).unwrap();
+        UnboundedGrid::set(game.grid_mut(), 0, 0, Cell::Alive);
+        game.grid_mut().update();
+        game.next();
+        assert_eq!(&Rule::HIGHLIFE, game.grid().rule());
+        assert_eq!(0, game.grid().population());
+    }
+    proptest! {
+        /// Tests advancing a random pattern gives the same cells
+        /// as evolving it one generation at a time.
+        #[test]
+        fn naive_comparison_test(
+            cells in proptest::collection::vec((-8i64..8, -8i64..8), 0..64),
+            log2_generations in 0u32..6,
+            rule in prop_oneof![
+                Just(Rule::CONWAY),
+                Just(Rule::HIGHLIFE),
+                Just(Rule::DAY_AND_NIGHT),
+                Just("B3/S2-i34q".parse().unwrap()),
+            ],
+            neighborhood in prop_oneof![
+                Just(Neighborhood::Moore),
+                Just(Neighborhood::Hexagonal),
+                Just(Neighborhood::VonNeumann),
+                Just(Neighborhood::ExtendedMoore(1)),
+            ],
+        ) {
+            let mut game = GameOfLife::<SparseGrid>::new(rule).unwrap().with_neighborhood(neighborhood.clone()).unwrap();
+            for &(x, y) in &cells {
+                game.grid_mut().set(x, y, Cell::Alive);
+            }
+            game.grid_mut().update();
+            let mut hashlife = HashLife::from_unbounded(game.grid(), rule).unwrap();
+            hashlife.set_neighborhood(neighborhood).unwrap();
+            for _ in 0..1 << log2_generations {
+                game.next();
+            }
+            hashlife.advance(log2_generations);
+            let mut expected = game.grid().alive_cells();
+            let mut alive = hashlife.alive_cells();
+            expected.sort();
+            alive.sort();
+            assert_eq!(expected, alive);
+            assert_eq!(game.grid().bounding_box(), hashlife.bounding_box());
+        }
+        /// Tests cells can be set and read back anywhere in the plane.
+        #[test]
+        fn get_set_test(x in any::<i64>(), y in any::<i64>()) {
+            let mut hashlife = HashLife::default();
+            hashlife.set(x, y, Cell::Alive);
+            assert_eq!(Cell::Dead, hashlife.get(x, y));
+            hashlife.update();
+            assert_eq!(Cell::Alive, hashlife.get(x, y));
+            assert_eq!(vec![(x, y)], hashlife.alive_cells());
+            hashlife.set(x, y, Cell::Dead);
+            hashlife.update();
+            assert_eq!(0, hashlife.population());
+        }
+    }
+}
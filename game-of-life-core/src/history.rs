@@ -0,0 +1,378 @@
+//! Undoing and redoing generations and edits of a [`GameOfLife`].
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{grid::Cell, GameOfLife, Universe};
+
+/// The cells which changed between two states of the game.
+struct Change {
+    /// The state of each changed cell before the change.
+    before: Vec<((i64, i64), Cell)>,
+    /// The state of each changed cell after the change.
+    after: Vec<((i64, i64), Cell)>,
+    /// The generation of the game before the change.
+    generation: u64,
+    /// True if the change was a generation, rather than an edit of the grid.
+    step: bool,
+}
+
+/// A bounded history of the changes made to a game.
+/// Only the cells which changed are stored, rather than copies of the grid.
+#[derive(Default)]
+pub(crate) struct History {
+    capacity: usize,
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    /// The cells which were not dead as of the last change recorded,
+    /// used to find which cells changed since.
+    recorded: HashMap<(i64, i64), Cell>,
+    /// True if the grid was lent out for editing since the last generation recorded,
+    /// so it may have changed without the history knowing.
+    /// Edits which were set but not yet updated can show up at any update until then.
+    edited: bool,
+}
+
+impl History {
+    /// Records the cells which changed since the last change recorded.
+    /// An edit which changed nothing is not recorded, while a generation always is.
+    fn record(&mut self, cells: Vec<((i64, i64), Cell)>, generation: u64, step: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        let cells: HashMap<(i64, i64), Cell> = cells.into_iter().collect();
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        for (&position, &new) in &cells {
+            let old = self.recorded.remove(&position).unwrap_or(Cell::Dead);
+            if old != new {
+                before.push((position, old));
+                after.push((position, new));
+            }
+        }
+        // What is left of the recorded cells has since died.
+        for (position, old) in self.recorded.drain() {
+            before.push((position, old));
+            after.push((position, Cell::Dead));
+        }
+        self.recorded = cells;
+        if before.is_empty() && !step {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(Change { before, after, generation, step });
+        if self.undo.len() > self.capacity {
+            self.undo.pop_front();
+        }
+    }
+    /// Sets the cells in the recorded cells,
+    /// so they match the universe once its cells have been set as well.
+    fn set(&mut self, cells: &[((i64, i64), Cell)]) {
+        for &(position, cell) in cells {
+            match cell {
+                Cell::Dead => self.recorded.remove(&position),
+                cell => self.recorded.insert(position, cell),
+            };
+        }
+    }
+}
+
+impl<T: Universe> GameOfLife<T> {
+    /// Returns the game remembering up to `capacity` changes which can be undone.
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.set_history_capacity(capacity);
+        self
+    }
+    /// Returns the number of changes which are remembered.
+    /// The history is off when this is 0, which it is by default.
+    pub fn history_capacity(&self) -> usize {
+        self.history.capacity
+    }
+    /// Changes the number of changes which are remembered,
+    /// forgetting the oldest changes which no longer fit.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.record_edit();
+        if self.history.capacity == 0 {
+            self.history.recorded = self.grid.cells().into_iter().collect();
+        }
+        self.history.capacity = capacity;
+        while self.history.undo.len() > capacity {
+            self.history.undo.pop_front();
+        }
+        if capacity == 0 {
+            self.history = History::default();
+        }
+    }
+    /// Reverses the last generation or edit of the grid.
+    /// Edits made through [`grid_mut`](GameOfLife::grid_mut) are
+    /// told apart by each call to [`grid_mut`](GameOfLife::grid_mut).
+    ///
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.record_edit();
+        let Some(change) = self.history.undo.pop_back() else {
+            return false;
+        };
+        self.grid.set_cells(&change.before);
+        self.history.set(&change.before);
+        self.generation = change.generation;
+        self.history.redo.push(change);
+        true
+    }
+    /// Repeats the last change which was undone.
+    /// Any new change made after undoing forgets the changes which could be redone.
+    ///
+    /// Returns false if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        self.record_edit();
+        let Some(change) = self.history.redo.pop() else {
+            return false;
+        };
+        self.grid.set_cells(&change.after);
+        self.history.set(&change.after);
+        self.generation = change.generation + u64::from(change.step);
+        self.history.undo.push_back(change);
+        true
+    }
+    /// Undoes changes until the game is back at `generation`,
+    /// as it was just before advancing past it.
+    ///
+    /// Returns false, leaving the game unchanged,
+    /// if the history does not reach back to `generation`.
+    pub fn rewind_to(&mut self, generation: u64) -> bool {
+        self.record_edit();
+        let reachable = match self.history.undo.front() {
+            _ if generation == self.generation => true,
+            Some(oldest) => oldest.generation <= generation && generation < self.generation,
+            None => false,
+        };
+        if !reachable {
+            return false;
+        }
+        while self.generation > generation {
+            self.undo();
+        }
+        true
+    }
+    /// Records any edits made to the grid since the last change recorded.
+    /// The grid can only be edited through [`grid_mut`](GameOfLife::grid_mut),
+    /// so it is only compared with the recorded cells after that was called.
+    pub(crate) fn record_edit(&mut self) {
+        if self.history.edited && self.history.capacity > 0 {
+            self.history.record(self.grid.cells(), self.generation, false);
+        }
+    }
+    /// Records any earlier edits, then notes the grid is about to be lent out for editing.
+    pub(crate) fn begin_edit(&mut self) {
+        self.record_edit();
+        self.history.edited = true;
+    }
+    /// Records the generation which was just calculated.
+    pub(crate) fn record_step(&mut self) {
+        // Evolving updates the grid, so any pending edits are part of the generation.
+        self.history.edited = false;
+        if self.history.capacity > 0 {
+            self.history.record(self.grid.cells(), self.generation - 1, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{
+        grid::{sparse::SparseGrid, Grid, UnboundedGrid},
+        rule::Rule,
+        tests::*,
+    };
+
+    #[test]
+    fn history_off_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        assert_eq!(0, game.history_capacity());
+        game.grid_mut().set(1, 1, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        game.next();
+        assert!(!game.undo());
+        assert_eq!(1, game.generation());
+    }
+    #[test]
+    fn undo_edit_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(10);
+        game.grid_mut().set(1, 1, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        game.grid_mut().set(2, 1, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Alive), game.grid().get(1, 1));
+        assert_eq!(Ok(Cell::Dead), game.grid().get(2, 1));
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Dead), game.grid().get(1, 1));
+        assert!(!game.undo());
+        assert!(game.redo());
+        assert!(game.redo());
+        assert_eq!(Ok(Cell::Alive), game.grid().get(1, 1));
+        assert_eq!(Ok(Cell::Alive), game.grid().get(2, 1));
+        assert!(!game.redo());
+    }
+    #[test]
+    fn undo_step_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(10);
+        for x in 1..=3 {
+            game.grid_mut().set(x, 2, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        let start = cells(game.grid());
+        game.next();
+        let next = cells(game.grid());
+        assert!(game.undo());
+        assert_eq!(0, game.generation());
+        assert_eq!(start, cells(game.grid()));
+        assert!(game.redo());
+        assert_eq!(1, game.generation());
+        assert_eq!(next, cells(game.grid()));
+    }
+    /// Tests a generation which changes nothing can still be undone.
+    #[test]
+    fn undo_still_step_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(10);
+        game.step(3);
+        assert!(game.undo());
+        assert_eq!(2, game.generation());
+    }
+    /// Tests a new change forgets the changes which could be redone.
+    #[test]
+    fn redo_cleared_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(10);
+        game.step(2);
+        assert!(game.undo());
+        game.grid_mut().set(0, 0, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        assert!(!game.redo());
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Dead), game.grid().get(0, 0));
+        assert!(game.redo());
+        assert_eq!(Ok(Cell::Alive), game.grid().get(0, 0));
+        assert_eq!(1, game.generation());
+    }
+    /// Tests only the newest changes are remembered.
+    #[test]
+    fn capacity_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(3);
+        game.step(5);
+        assert!(!game.rewind_to(1));
+        assert!(game.rewind_to(2));
+        assert_eq!(2, game.generation());
+        assert!(!game.undo());
+        game.set_history_capacity(0);
+        game.next();
+        assert!(!game.undo());
+    }
+    /// Tests an edit which was set but not yet updated
+    /// is recorded once it is updated, rather than lost by undoing.
+    #[test]
+    fn pending_edit_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(10);
+        game.grid_mut().set(5, 5, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        game.grid_mut().set(6, 6, Cell::Alive).unwrap();
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Dead), game.grid().get(5, 5));
+        assert_eq!(Ok(Cell::Alive), game.grid().get(6, 6));
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Dead), game.grid().get(6, 6));
+    }
+    #[test]
+    fn unbounded_undo_test() {
+        let mut game = GameOfLife::<SparseGrid>::default().with_history(10);
+        for (x, y) in [(-1, 0), (0, 0), (1, 0)] {
+            game.grid_mut().set(x, y, Cell::Alive);
+        }
+        game.grid_mut().update();
+        game.next();
+        assert_eq!(Cell::Alive, game.grid().get(0, -1));
+        assert!(game.undo());
+        assert_eq!(Cell::Dead, game.grid().get(0, -1));
+        assert_eq!(Cell::Alive, game.grid().get(-1, 0));
+    }
+    /// Tests undoing a generation brings back cells which were dying.
+    #[test]
+    fn undo_dying_test() {
+        let mut game = GameOfLife::<TestGrid>::new(Rule::STAR_WARS).unwrap().with_history(10);
+        game.grid_mut().set(1, 1, Cell::Dying(2)).unwrap();
+        game.grid_mut().set(5, 5, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        game.next();
+        assert_eq!(Ok(Cell::Dead), game.grid().get(1, 1));
+        assert_eq!(Ok(Cell::Dying(1)), game.grid().get(5, 5));
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Dying(2)), game.grid().get(1, 1));
+        assert_eq!(Ok(Cell::Alive), game.grid().get(5, 5));
+    }
+    /// Tests the grid is only compared with the history after it was lent out for editing.
+    #[test]
+    fn edited_test() {
+        let mut game = GameOfLife::<TestGrid>::default().with_history(10);
+        game.step(2);
+        assert!(!game.history.edited);
+        game.grid_mut().set(0, 0, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        assert!(game.history.edited);
+        assert!(game.undo());
+        assert!(game.history.edited);
+        assert_eq!(Ok(Cell::Dead), game.grid().get(0, 0));
+        assert!(game.redo());
+        game.next();
+        assert!(!game.history.edited);
+        assert!(game.undo());
+        assert!(game.undo());
+        assert_eq!(Ok(Cell::Dead), game.grid().get(0, 0));
+        assert_eq!(2, game.generation());
+    }
+    proptest! {
+        /// Tests rewinding to any earlier generation gives back
+        /// the grid as it was at that generation.
+        #[test]
+        fn rewind_test(
+            cells_set in proptest::collection::vec((0..WIDTH, 0..HEIGHT), 0..60),
+            generations in 1u64..20,
+            rewind in 0u64..20,
+        ) {
+            let rewind = rewind % generations;
+            let mut game = GameOfLife::<TestGrid>::default().with_history(20);
+            for (x, y) in cells_set {
+                game.grid_mut().set(x, y, Cell::Alive).unwrap();
+            }
+            game.grid_mut().update();
+            let mut expected = Vec::new();
+            for _ in 0..generations {
+                expected.push(cells(game.grid()));
+                game.next();
+            }
+            let last = cells(game.grid());
+            assert!(game.rewind_to(rewind));
+            assert_eq!(rewind, game.generation());
+            assert_eq!(expected[rewind as usize], cells(game.grid()));
+            while game.redo() {}
+            assert_eq!(generations, game.generation());
+            assert_eq!(last, cells(game.grid()));
+        }
+    }
+
+
+    // Helper Functions
+    // These are called in tests above.
+
+    /// Returns the coordinates of every alive cell in the grid.
+    fn cells(grid: &impl Grid) -> Vec<(usize, usize)> {
+        let mut alive = Vec::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.get(x, y).unwrap() == Cell::Alive {
+                    alive.push((x, y));
+                }
+            }
+        }
+        alive
+    }
+}
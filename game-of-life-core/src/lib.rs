@@ -1,180 +1,839 @@
-use grid::{Cell, Grid};
+use grid::{neighborhood::Neighborhood, topology::Topology, Cell, CellState, Grid};
+use hashlife::UnsupportedRuleError;
+use history::History;
+use random::Rng;
+use stochastic::Stochastic;
+use rule::{Rule, Transition};
 
+pub mod cycle;
+pub mod elementary;
 pub mod grid;
+pub mod hashlife;
+mod history;
+pub mod margolus;
+pub mod pattern;
+pub mod random;
+pub mod rule;
+pub mod stochastic;
+pub mod wireworld;
 
-pub struct GameOfLife<T: Grid> {
+/// A space of cells which [`GameOfLife`] is able to evolve.
+/// Every [`Grid`] is a universe.
+pub trait Universe {
+    /// Changes every cell to its state in the next generation under `rule`,
+    /// with the edges of the universe joined by `topology`
+    /// and the neighbors of each cell chosen by `neighborhood`.
+    fn evolve(&mut self, rule: &Rule, topology: Topology, neighborhood: &Neighborhood);
+    /// Returns the coordinates and state of every cell which is not dead, in no particular order.
+    fn cells(&self) -> Vec<((i64, i64), Cell)>;
+    /// Sets each of the cells to its state, then updates the universe.
+    fn set_cells(&mut self, cells: &[((i64, i64), Cell)]);
+    /// Returns true if the universe has no edges,
+    /// so a pattern can travel through it forever.
+    fn is_unbounded(&self) -> bool;
+    /// Returns Err if the universe can not evolve under `rule` in `neighborhood`,
+    /// which [`GameOfLife`] checks before changing either of them.
+    /// Every rule and neighborhood is supported unless a universe says otherwise.
+    fn check_rule(&self, _rule: &Rule, _neighborhood: &Neighborhood) -> Result<(), UnsupportedRuleError> {
+        Ok(())
+    }
+}
+
+impl<T: Grid> Universe for T {
+    fn evolve(&mut self, rule: &Rule, topology: Topology, neighborhood: &Neighborhood) {
+        self.next_generation(rule, topology, neighborhood);
+    }
+    fn cells(&self) -> Vec<((i64, i64), Cell)> {
+        let mut cells = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                match self.get(x, y) {
+                    Ok(Cell::Dead) | Err(_) => {},
+                    Ok(cell) => cells.push(((x as i64, y as i64), cell)),
+                }
+            }
+        }
+        cells
+    }
+    /// Cells outside of the grid are ignored.
+    fn set_cells(&mut self, cells: &[((i64, i64), Cell)]) {
+        for &((x, y), cell) in cells {
+            if let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) {
+                let _ = self.set(x, y, cell);
+            }
+        }
+        self.update();
+    }
+    fn is_unbounded(&self) -> bool {
+        false
+    }
+}
+
+/// Changes every cell of the grid to its state in the next generation,
+/// by counting the neighbors of each cell one at a time.
+/// This is what [`Grid::next_generation`] does unless a grid provides a faster way.
+/// Rules which are not [totalistic](Rule::is_totalistic) look at the arrangement of the neighbors instead
+/// when they are the [Moore neighbors](Neighborhood::is_moore), as arrangements are only named for those,
+/// and only count them in any other neighborhood.
+pub fn evolve_each_cell(grid: &mut impl Grid, rule: &Rule, topology: Topology, neighborhood: &Neighborhood) {
+    if !rule.is_totalistic() && neighborhood.is_moore() {
+        // The Moore neighborhood lists its neighbors in the order arrangements are read in,
+        // however the neighborhood given lists them.
+        return evolve_with(grid, rule, topology, &Neighborhood::Moore);
+    }
+    let offsets = neighborhood.offsets();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            // We know the cell is in bounds
+            // because we use the grid's width and height
+            // for coordinates.
+            // TODO: use an iterator that is provided by the grid
+            // instead of manually accessing the indexes.
+            let cell = grid.get(x, y).unwrap();
+            let count = topology.neighbor_count_at(grid, &offsets, x, y).unwrap();
+            grid.set(x, y, rule.next_cell(cell, count)).unwrap();
+        }
+    }
+    grid.update();
+}
+
+/// Changes every cell of a grid of any state to its state in the next generation,
+/// as decided by `transition` from the cell and its neighbors in `neighborhood`,
+/// with the edges of the grid joined by `topology`.
+pub fn evolve_with<S: CellState>(
+    grid: &mut impl Grid<S>,
+    transition: &impl Transition<S>,
+    topology: Topology,
+    neighborhood: &Neighborhood,
+) {
+    let offsets = neighborhood.offsets();
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            // We know the cell is in bounds
+            // because we use the grid's width and height
+            // for coordinates.
+            let cell = grid.get(x, y).unwrap();
+            let neighbors = topology.neighbors_at(grid, &offsets, x, y).unwrap();
+            grid.set(x, y, transition.next_state(cell, &neighbors)).unwrap();
+        }
+    }
+    grid.update();
+}
+
+pub struct GameOfLife<T: Universe> {
     grid: T,
+    rule: Rule,
+    topology: Topology,
+    neighborhood: Neighborhood,
+    generation: u64,
+    history: History,
+    stochastic: Stochastic,
+    rng: Rng,
 }
 
-impl<T: Grid> Default for GameOfLife<T> {
+impl<T: Universe + Default> Default for GameOfLife<T> {
     fn default() -> Self {
-        GameOfLife { grid: T::default() }
+        // We know every universe supports Conway's rule
+        // in the Moore neighborhood.
+        GameOfLife::new(Rule::default()).unwrap()
     }
 }
 
-impl<T: Grid> GameOfLife<T> {
+impl<T: Universe + Default> GameOfLife<T> {
+    /// Creates a game with an empty grid that evolves under `rule`.
+    /// Returns Err if the grid does not support the rule, see [`Universe::check_rule`].
+    pub fn new(rule: Rule) -> Result<Self, UnsupportedRuleError> {
+        GameOfLife::from_grid(T::default()).with_rule(rule)
+    }
+}
+
+impl<T: Universe> GameOfLife<T> {
+    /// Creates a game which evolves an existing grid under Conway's rule.
+    pub fn from_grid(grid: T) -> Self {
+        GameOfLife {
+            grid,
+            rule: Rule::default(),
+            topology: Topology::default(),
+            neighborhood: Neighborhood::default(),
+            generation: 0,
+            history: History::default(),
+            stochastic: Stochastic::default(),
+            rng: Rng::new(0),
+        }
+    }
+    /// Returns the game evolving under `rule` instead,
+    /// or Err if the grid does not support the rule, see [`Universe::check_rule`].
+    pub fn with_rule(mut self, rule: Rule) -> Result<Self, UnsupportedRuleError> {
+        self.set_rule(rule)?;
+        Ok(self)
+    }
+    /// Returns the game joining the grid's edges by `topology` instead.
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+    /// Returns the game counting the neighbors in `neighborhood` instead of the Moore neighborhood,
+    /// or Err if the grid does not support the neighborhood, see [`Universe::check_rule`].
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Result<Self, UnsupportedRuleError> {
+        self.set_neighborhood(neighborhood)?;
+        Ok(self)
+    }
     pub fn next(&mut self) {
-        for y in 0..self.grid.height() {
-            for x in 0..self.grid.width() {
-                // We know the neighbor is in bounds
-                // because we use the grid's width and height
-                // for coordinates.
-                // TODO: use an iterator that is provided by the grid
-                // instead of manually accessing the indexes.
-                let count = self.grid.neighbor_count(x, y).unwrap();
-                match count {
-                    0..=1 => {
-                        // Death
-                        self.grid.set(x, y, Cell::Dead).unwrap();
-                    },
-                    2 => {
-                        // Stayin' Alive (or Dead)
-                    },
-                    3 => {
-                        // Reproduction
-                        self.grid.set(x, y, Cell::Alive).unwrap();
-                    }
-                    _ => {
-                        // Overpopulation
-                        self.grid.set(x, y, Cell::Dead).unwrap();
-                    },
-                }
+        self.record_edit();
+        if self.stochastic.is_deterministic() {
+            self.grid.evolve(&self.rule, self.topology, &self.neighborhood);
+        } else {
+            self.evolve_stochastically();
+        }
+        self.generation += 1;
+        self.record_step();
+    }
+    /// Advances the game by `generations` generations.
+    pub fn step(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.next();
+        }
+    }
+    /// Returns the number of generations the game has advanced since it was created.
+    /// Changing cells through [`grid_mut`](GameOfLife::grid_mut) does not count as a generation.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    /// Returns an iterator which advances the game one generation at a time,
+    /// yielding the cells which are not dead after each generation, as in [`Universe::cells`].
+    /// The iterator never ends, so limit it with [`Iterator::take`] or similar.
+    pub fn generations(&mut self) -> Generations<'_, T> {
+        Generations { game: self }
+    }
+    /// Advances the game until `predicate` holds for the grid,
+    /// or the game reaches generation `max_generation`.
+    /// The grid is checked before the first generation, so a game which already
+    /// satisfies `predicate` is not advanced.
+    ///
+    /// Returns the generation `predicate` held at,
+    /// or None if `max_generation` was reached first.
+    pub fn run_until(&mut self, mut predicate: impl FnMut(&T) -> bool, max_generation: u64) -> Option<u64> {
+        loop {
+            if predicate(&self.grid) {
+                return Some(self.generation);
             }
+            if self.generation >= max_generation {
+                return None;
+            }
+            self.next();
         }
-        self.grid.update();
+    }
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+    /// Returns Err without changing the rule if the grid does not support it.
+    pub fn set_rule(&mut self, rule: Rule) -> Result<(), UnsupportedRuleError> {
+        self.grid.check_rule(&rule, &self.neighborhood)?;
+        self.rule = rule;
+        Ok(())
+    }
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+    pub fn neighborhood(&self) -> &Neighborhood {
+        &self.neighborhood
+    }
+    /// Returns Err without changing the neighborhood if the grid does not support it.
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) -> Result<(), UnsupportedRuleError> {
+        self.grid.check_rule(&self.rule, &neighborhood)?;
+        self.neighborhood = neighborhood;
+        Ok(())
     }
     pub fn grid(&self) -> &T {
         &self.grid
     }
+    /// Returns the grid to be edited.
+    /// When the history is on, edits made since the last call are recorded first,
+    /// so each call can be undone on its own.
     pub fn grid_mut(&mut self) -> &mut T {
+        self.begin_edit();
         &mut self.grid
     }
 }
 
+/// An iterator over the generations of a [`GameOfLife`],
+/// created by [`GameOfLife::generations`].
+pub struct Generations<'a, T: Universe> {
+    game: &'a mut GameOfLife<T>,
+}
+
+impl<T: Universe> Iterator for Generations<'_, T> {
+    type Item = Vec<((i64, i64), Cell)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.game.next();
+        Some(self.game.grid.cells())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use self::grid::obj::GameOfLifeGrid;
+    use self::grid::{
+        bit::BitGrid, dynamic::DynGrid, obj::GameOfLifeGrid, offset_positions, sparse::SparseGrid, Cell, UnboundedGrid,
+        NEIGHBOR_OFFSETS,
+    };
     use proptest::prelude::*;
 
     pub const WIDTH: usize = 12;
     pub const HEIGHT: usize = 12;
     pub type TestGrid = GameOfLifeGrid<WIDTH, HEIGHT>;
+    /// Repeats the tests in a module of their own for each size of grid,
+    /// where `WIDTH`, `HEIGHT` and `TestGrid` are of that size.
+    ///
+    /// The sizes are a square, a single column, a single row, a wide grid and a tall grid.
+    /// Tests which need room around a cell on both axes can be left out of the column and the row
+    /// with `mod name with room`, which keeps each side at least 5 cells long.
+    ///
+    /// `TestGrid` is a [`GameOfLifeGrid`] unless other grids are listed by name,
+    /// ex. `mod name for [obj: GameOfLifeGrid, bit: BitGrid]`,
+    /// which repeats the tests for each of them as well.
+    macro_rules! sized_tests {
+        (mod $group:ident $(with $room:ident)? { $($tests:tt)* }) => {
+            sized_tests!(mod $group for [obj: crate::grid::obj::GameOfLifeGrid] $(with $room)? { $($tests)* });
+        };
+        (mod $group:ident for $grids:tt { $($tests:tt)* }) => {
+            sized_tests!(@grids $group $grids [square: 12 x 12, column: 1 x 7, row: 7 x 1, wide: 16 x 5, tall: 5 x 16] { $($tests)* });
+        };
+        (mod $group:ident for $grids:tt with room { $($tests:tt)* }) => {
+            sized_tests!(@grids $group $grids [square: 12 x 12, wide: 16 x 5, tall: 5 x 16] { $($tests)* });
+        };
+        (@grids $group:ident [$($grid:ident: $($path:ident)::+),*] $sizes:tt $tests:tt) => {
+            mod $group {
+                use super::*;
+                $( sized_tests!(@sizes $grid ($($path)::+) $sizes $tests); )*
+            }
+        };
+        (@sizes $grid:ident $path:tt [$($size:ident: $width:literal x $height:literal),*] $tests:tt) => {
+            mod $grid {
+                use super::*;
+                $( sized_tests!(@size $size $width $height $path $tests); )*
+            }
+        };
+        (@size $size:ident $width:literal $height:literal ($($path:ident)::+) { $($tests:tt)* }) => {
+            mod $size {
+                use super::*;
+                const WIDTH: usize = $width;
+                const HEIGHT: usize = $height;
+                type TestGrid = $($path)::+<WIDTH, HEIGHT>;
+                $($tests)*
+            }
+        };
+    }
+    pub(crate) use sized_tests;
 
-    proptest! {
-        /// Tests death of cell via underpopulation,
-        /// because it is alone.
-        #[test]
-        fn next_alone_death_test(x in 0..WIDTH, y in 0..HEIGHT) {
-            let mut game = GameOfLife::<TestGrid>::default();
-            game.grid_mut().set(x, y, Cell::Alive).unwrap();
-            game.grid_mut().update();
-            let cell = game.grid().get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Alive, cell.unwrap());
-            game.next();
-            let cell = game.grid().get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Dead, cell.unwrap());
-        }
-        /// Tests death of cell via underpopulation,
-        /// but not alone.
-        #[test]
-        fn next_underpopulation_death_test(x in 0..WIDTH-1, y in 0..HEIGHT) {
-            let mut game = GameOfLife::<TestGrid>::default();
-            game.grid_mut().set(x, y, Cell::Alive).unwrap();
-            game.grid_mut().set(x + 1, y, Cell::Alive).unwrap();
-            game.grid_mut().update();
-            let cell = game.grid().get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Alive, cell.unwrap());
-            let cell = game.grid().get(x + 1, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Alive, cell.unwrap());
-            game.next();
-            let cell = game.grid().get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Dead, cell.unwrap());
-            let cell = game.grid().get(x + 1, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Dead, cell.unwrap());
-        }
-        /// Tests death of cell via overpopulation.
-        #[test]
-        fn next_overpopulation_death_test(x in 1..WIDTH-1, y in 0..HEIGHT-1) {
-            let mut game = GameOfLife::<TestGrid>::default();
-            game.grid_mut().set(x, y, Cell::Alive).unwrap();
-            game.grid_mut().set(x + 1, y, Cell::Alive).unwrap();
-            game.grid_mut().set(x + 1, y + 1, Cell::Alive).unwrap();
-            game.grid_mut().set(x, y + 1, Cell::Alive).unwrap();
-            game.grid_mut().set(x - 1, y + 1, Cell::Alive).unwrap();
-            game.grid_mut().update();
-            let cell = game.grid().get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Alive, cell.unwrap());
-            game.next();
-            let cell = game.grid().get(x, y);
-            assert!(cell.is_ok());
-            assert_eq!(Cell::Dead, cell.unwrap());
-        }
-        /// Tests production of a cell, and the staying alive of cells.
-        #[test]
-        fn next_produce_square_pattern_test(x in 1..WIDTH-1, y in 1..HEIGHT-1) {
-            let mut game = GameOfLife::<TestGrid>::default();
-            // Keep cell at (1, 1) alive
-            game.grid_mut().set(x, y, Cell::Alive).unwrap();
-            game.grid_mut().set(x + 1, y, Cell::Alive).unwrap();
-            game.grid_mut().set(x, y + 1, Cell::Alive).unwrap();
-            game.grid_mut().update();
-            // Run behavior a few times
-            // state should not change after
-            // first call of `next`
-            for _ in 0..4 {
-                // Next should keep all cells alive,
-                // AND make a fourth one at (2, 2)
-                game.next();
-                // User Activated Cells
+    sized_tests! { mod sized for [obj: GameOfLifeGrid, bit: BitGrid] {
+        proptest! {
+            /// Tests death of cell via underpopulation,
+            /// because it is alone.
+            #[test]
+            fn next_alone_death_test(x in 0..WIDTH, y in 0..HEIGHT) {
+                let mut game = GameOfLife::<TestGrid>::default();
+                game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                game.grid_mut().update();
                 let cell = game.grid().get(x, y);
                 assert!(cell.is_ok());
                 assert_eq!(Cell::Alive, cell.unwrap());
-                let cell = game.grid().get(x + 1, y);
+                game.next();
+                let cell = game.grid().get(x, y);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Dead, cell.unwrap());
+            }
+            #[test]
+            fn keep_grid_test(x in 0..WIDTH, y in 0..HEIGHT) {
+                let mut grid = GameOfLife::<TestGrid>::default();
+                let r = grid.grid.set(x, y, Cell::Alive);
+                grid.grid.update();
+                assert!(r.is_ok());
+                let r = grid.grid().get(x, y);
+                assert!(r.is_ok());
+                let r = r.unwrap();
+                let expected = grid.grid.get(x, y);
+                assert!(expected.is_ok());
+                assert_eq!(expected.unwrap(), r);
+            }
+            #[test]
+            fn keep_grid_mut_test(x in 0..WIDTH, y in 0..HEIGHT) {
+                let mut grid = GameOfLife::<TestGrid>::default();
+                let r = grid.grid.set(x, y, Cell::Alive);
+                grid.grid.update();
+                assert!(r.is_ok());
+                let r = grid.grid_mut().get(x, y);
+                assert!(r.is_ok());
+                let r = r.unwrap();
+                let expected = grid.grid.get(x, y);
+                assert!(expected.is_ok());
+                assert_eq!(expected.unwrap(), r);
+            }
+            /// Tests death of cell via underpopulation,
+            /// but not alone.
+            #[test]
+            fn next_underpopulation_death_test(i in 0..WIDTH.max(HEIGHT) - 1, j in 0..WIDTH.min(HEIGHT)) {
+                let (x, y) = along(WIDTH, HEIGHT, i, j);
+                let (nx, ny) = along(WIDTH, HEIGHT, i + 1, j);
+                let mut game = GameOfLife::<TestGrid>::default();
+                game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                game.grid_mut().set(nx, ny, Cell::Alive).unwrap();
+                game.grid_mut().update();
+                let cell = game.grid().get(x, y);
                 assert!(cell.is_ok());
                 assert_eq!(Cell::Alive, cell.unwrap());
-                let cell = game.grid().get(x, y + 1);
+                let cell = game.grid().get(nx, ny);
                 assert!(cell.is_ok());
                 assert_eq!(Cell::Alive, cell.unwrap());
-                // Fourth Automatic Activated Cell
-                // because we called `next`
-                let cell = game.grid().get(x + 1, y + 1);
+                game.next();
+                let cell = game.grid().get(x, y);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Dead, cell.unwrap());
+                let cell = game.grid().get(nx, ny);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Dead, cell.unwrap());
+            }
+            /// Tests every alive cell dies under Seeds,
+            /// while dead cells with two neighbors are born,
+            /// which are the cell between two alive cells, and the cells either side of it where there is room.
+            #[test]
+            fn next_seeds_test(i in 1..WIDTH.max(HEIGHT) - 1, j in 0..WIDTH.min(HEIGHT)) {
+                let mut game = GameOfLife::<TestGrid>::new(Rule::SEEDS).unwrap();
+                let [(bx, by), (x, y), (ax, ay)] = [i - 1, i, i + 1].map(|i| along(WIDTH, HEIGHT, i, j));
+                game.grid_mut().set(bx, by, Cell::Alive).unwrap();
+                game.grid_mut().set(ax, ay, Cell::Alive).unwrap();
+                game.grid_mut().update();
+                game.next();
+                assert_eq!(Cell::Dead, game.grid().get(bx, by).unwrap());
+                assert_eq!(Cell::Dead, game.grid().get(ax, ay).unwrap());
+                assert_eq!(Cell::Alive, game.grid().get(x, y).unwrap());
+                let mut expected: Vec<_> = beside(j, WIDTH.min(HEIGHT)).map(|j| along(WIDTH, HEIGHT, i, j)).collect();
+                expected.push((x, y));
+                expected.sort_by_key(|&(x, y)| (y, x));
+                assert_eq!(expected, cells(game.grid()));
+            }
+            /// Tests cells of Brian's Brain are born with two neighbors,
+            /// then always die after a single dying generation.
+            #[test]
+            fn next_brians_brain_test(i in 0..WIDTH.max(HEIGHT) - 1, j in 0..WIDTH.min(HEIGHT)) {
+                let mut game = GameOfLife::<TestGrid>::new(Rule::BRIANS_BRAIN).unwrap();
+                let pair = [along(WIDTH, HEIGHT, i, j), along(WIDTH, HEIGHT, i + 1, j)];
+                for (x, y) in pair {
+                    game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                }
+                game.grid_mut().update();
+                game.next();
+                for (x, y) in pair {
+                    assert_eq!(Cell::Dying(1), game.grid().get(x, y).unwrap());
+                }
+                // The cells beside the pair on either side are born, where there is room for them.
+                let born: Vec<_> = beside(j, WIDTH.min(HEIGHT))
+                    .flat_map(|j| [along(WIDTH, HEIGHT, i, j), along(WIDTH, HEIGHT, i + 1, j)])
+                    .collect();
+                for &(nx, ny) in &born {
+                    assert_eq!(Cell::Alive, game.grid().get(nx, ny).unwrap());
+                }
+                assert_eq!(born.len(), game.grid().population());
+                game.next();
+                for (x, y) in pair {
+                    assert_eq!(Cell::Dead, game.grid().get(x, y).unwrap());
+                }
+            }
+            /// Tests a lone cell births the four cells beside it in the von Neumann neighborhood,
+            /// and the six cells around it in the hexagonal neighborhood, where there is room for them.
+            #[test]
+            fn next_neighborhood_test(x in 0..WIDTH, y in 0..HEIGHT) {
+                let around = |offsets: &[(isize, isize)]| -> Vec<(usize, usize)> {
+                    offset_positions(x, y, offsets.iter().copied()).filter(|&(nx, ny)| nx < WIDTH && ny < HEIGHT).collect()
+                };
+                for (neighborhood, expected) in [
+                    (Neighborhood::VonNeumann, around(&[(0, -1), (-1, 0), (1, 0), (0, 1)])),
+                    (Neighborhood::Hexagonal, around(&[(-1, -1), (0, -1), (-1, 0), (1, 0), (0, 1), (1, 1)])),
+                ] {
+                    let mut game = GameOfLife::<TestGrid>::new("B1/S".parse().unwrap()).unwrap().with_neighborhood(neighborhood).unwrap();
+                    game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                    game.grid_mut().update();
+                    game.next();
+                    assert_eq!(expected, cells(game.grid()));
+                }
+            }
+            /// Tests a rule which is not totalistic reads the arrangement of the neighbors the same
+            /// whichever order a neighborhood of the eight Moore neighbors lists them in,
+            /// and only counts the neighbors of other neighborhoods.
+            #[test]
+            fn next_moore_in_any_order_test(
+                alive in prop::collection::vec((0..WIDTH, 0..HEIGHT), 0..40),
+                rule in prop::sample::select(vec!["B2a/S", "B2c/S", "B3-a/S23", "B2-a/S12", "B3q/S2-i34q"]),
+                shuffled in Just(NEIGHBOR_OFFSETS.to_vec()).prop_shuffle(),
+            ) {
+                let rule: Rule = rule.parse().unwrap();
+                let new_game = |neighborhood| {
+                    let mut game = GameOfLife::<TestGrid>::new(rule).unwrap().with_neighborhood(neighborhood).unwrap();
+                    for &(x, y) in &alive {
+                        game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                    }
+                    game.grid_mut().update();
+                    game
+                };
+                let mut expected = new_game(Neighborhood::Moore);
+                let mut games = [new_game(Neighborhood::ExtendedMoore(1)), new_game(Neighborhood::Custom(shuffled.clone()))];
+                for _ in 0..3 {
+                    expected.next();
+                    for game in &mut games {
+                        game.next();
+                        assert_eq!(cells(expected.grid()), cells(game.grid()));
+                    }
+                }
+                let mut counted = new_game(Neighborhood::ExtendedMoore(2));
+                let mut grid = counted.grid().clone();
+                counted.next();
+                for y in 0..HEIGHT {
+                    for x in 0..WIDTH {
+                        let count = Topology::Bounded.neighbor_count(&grid, &Neighborhood::ExtendedMoore(2), x, y).unwrap();
+                        grid.set(x, y, rule.next_cell(grid.get(x, y).unwrap(), count)).unwrap();
+                    }
+                }
+                grid.update();
+                assert_eq!(cells(&grid), cells(counted.grid()));
+            }
+            /// Tests two games with the same seed, grid and probabilities
+            /// play out the same, generation by generation.
+            #[test]
+            fn stochastic_seed_test(
+                alive in prop::collection::vec((0..WIDTH, 0..HEIGHT), 0..60),
+                seed: u64,
+                birth in 0.0..=1.0,
+                survival in 0.0..=1.0,
+                update in 0.0..=1.0,
+            ) {
+                let stochastic = Stochastic { birth, survival, update };
+                let new_game = || {
+                    let mut game = GameOfLife::<TestGrid>::default().with_stochastic(stochastic).with_seed(seed);
+                    for &(x, y) in &alive {
+                        game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                    }
+                    game.grid_mut().update();
+                    game
+                };
+                let (mut first, mut second) = (new_game(), new_game());
+                for _ in 0..6 {
+                    first.next();
+                    second.next();
+                    assert_eq!(cells(first.grid()), cells(second.grid()));
+                }
+                // Restarting from the seed replays the same generations.
+                let mut replay = new_game();
+                replay.step(3);
+                replay.set_seed(seed);
+                let mut restarted = GameOfLife::<TestGrid>::default().with_stochastic(stochastic).with_seed(seed);
+                restarted.grid_mut().set_cells(&replay.grid().cells());
+                replay.step(3);
+                restarted.step(3);
+                assert_eq!(cells(replay.grid()), cells(restarted.grid()));
+            }
+            /// Tests a game where cells never update never changes,
+            /// and one where every probability is certain follows its rule exactly.
+            #[test]
+            fn stochastic_extremes_test(alive in prop::collection::vec((0..WIDTH, 0..HEIGHT), 0..60), seed: u64) {
+                let mut frozen = GameOfLife::<TestGrid>::default()
+                    .with_stochastic(Stochastic { update: 0.0, ..Stochastic::default() })
+                    .with_seed(seed);
+                let mut certain = GameOfLife::<TestGrid>::default()
+                    .with_stochastic(Stochastic { birth: 2.0, ..Stochastic::default() })
+                    .with_seed(seed);
+                let mut expected = GameOfLife::<TestGrid>::default();
+                for game in [&mut frozen, &mut certain, &mut expected] {
+                    for &(x, y) in &alive {
+                        game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                    }
+                    game.grid_mut().update();
+                }
+                let original = cells(frozen.grid());
+                for _ in 0..4 {
+                    frozen.next();
+                    certain.next();
+                    expected.next();
+                    assert_eq!(original, cells(frozen.grid()));
+                    assert_eq!(cells(expected.grid()), cells(certain.grid()));
+                }
+            }
+            /// Tests evolving a grid with a rule as its transition
+            /// gives the same generation as the rule itself, on every topology.
+            #[test]
+            fn evolve_with_rule_test(alive in prop::collection::vec((0..WIDTH, 0..HEIGHT), 0..40), brians_brain: bool) {
+                let rule = if brians_brain { Rule::BRIANS_BRAIN } else { Rule::CONWAY };
+                for topology in [Topology::Bounded, Topology::Torus, Topology::KleinBottle] {
+                    let mut expected = TestGrid::default();
+                    for &(x, y) in &alive {
+                        expected.set(x, y, Cell::Alive).unwrap();
+                    }
+                    expected.update();
+                    let mut grid = expected.clone();
+                    for _ in 0..3 {
+                        expected.next_generation(&rule, topology, &Neighborhood::Moore);
+                        evolve_with(&mut grid, &rule, topology, &Neighborhood::Moore);
+                        assert_eq!(expected.cells(), grid.cells());
+                    }
+                }
+            }
+        }
+    }}
+    sized_tests! { mod two_axes for [obj: GameOfLifeGrid, bit: BitGrid] with room {
+        proptest! {
+            /// Tests death of cell via overpopulation.
+            #[test]
+            fn next_overpopulation_death_test(x in 1..WIDTH-1, y in 0..HEIGHT-1) {
+                let mut game = GameOfLife::<TestGrid>::default();
+                game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                game.grid_mut().set(x + 1, y, Cell::Alive).unwrap();
+                game.grid_mut().set(x + 1, y + 1, Cell::Alive).unwrap();
+                game.grid_mut().set(x, y + 1, Cell::Alive).unwrap();
+                game.grid_mut().set(x - 1, y + 1, Cell::Alive).unwrap();
+                game.grid_mut().update();
+                let cell = game.grid().get(x, y);
                 assert!(cell.is_ok());
                 assert_eq!(Cell::Alive, cell.unwrap());
+                game.next();
+                let cell = game.grid().get(x, y);
+                assert!(cell.is_ok());
+                assert_eq!(Cell::Dead, cell.unwrap());
+            }
+            /// Tests production of a cell, and the staying alive of cells.
+            #[test]
+            fn next_produce_square_pattern_test(x in 1..WIDTH-1, y in 1..HEIGHT-1) {
+                let mut game = GameOfLife::<TestGrid>::default();
+                // Keep cell at (1, 1) alive
+                game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                game.grid_mut().set(x + 1, y, Cell::Alive).unwrap();
+                game.grid_mut().set(x, y + 1, Cell::Alive).unwrap();
+                game.grid_mut().update();
+                // Run behavior a few times
+                // state should not change after
+                // first call of `next`
+                for _ in 0..4 {
+                    // Next should keep all cells alive,
+                    // AND make a fourth one at (2, 2)
+                    game.next();
+                    // User Activated Cells
+                    let cell = game.grid().get(x, y);
+                    assert!(cell.is_ok());
+                    assert_eq!(Cell::Alive, cell.unwrap());
+                    let cell = game.grid().get(x + 1, y);
+                    assert!(cell.is_ok());
+                    assert_eq!(Cell::Alive, cell.unwrap());
+                    let cell = game.grid().get(x, y + 1);
+                    assert!(cell.is_ok());
+                    assert_eq!(Cell::Alive, cell.unwrap());
+                    // Fourth Automatic Activated Cell
+                    // because we called `next`
+                    let cell = game.grid().get(x + 1, y + 1);
+                    assert!(cell.is_ok());
+                    assert_eq!(Cell::Alive, cell.unwrap());
+                }
+            }
+            /// Tests a dead cell with six neighbors is born under HighLife,
+            /// but stays dead under Conway's rule.
+            #[test]
+            fn next_highlife_birth_test(x in 1..WIDTH-1, y in 1..HEIGHT-1) {
+                for (rule, expected) in [(Rule::HIGHLIFE, Cell::Alive), (Rule::CONWAY, Cell::Dead)] {
+                    let mut game = GameOfLife::<TestGrid>::new(rule).unwrap();
+                    for (nx, ny) in [(x - 1, y - 1), (x, y - 1), (x + 1, y - 1), (x - 1, y + 1), (x, y + 1), (x + 1, y + 1)] {
+                        game.grid_mut().set(nx, ny, Cell::Alive).unwrap();
+                    }
+                    game.grid_mut().update();
+                    game.next();
+                    let cell = game.grid().get(x, y);
+                    assert!(cell.is_ok());
+                    assert_eq!(expected, cell.unwrap());
+                }
+            }
+            /// Tests a glider travels across the edges of a torus,
+            /// returning to where it started after moving one cell diagonally
+            /// every four generations until it has crossed both the width and the height a whole number of times.
+            #[test]
+            fn torus_glider_test(x in 0..WIDTH, y in 0..HEIGHT) {
+                let period = 4 * lcm(WIDTH, HEIGHT);
+                let mut game = GameOfLife::<TestGrid>::default().with_topology(Topology::Torus);
+                for (gx, gy) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+                    game.grid_mut().set((x + gx) % WIDTH, (y + gy) % HEIGHT, Cell::Alive).unwrap();
+                }
+                game.grid_mut().update();
+                let start = cells(game.grid());
+                assert_eq!(5, start.len());
+                for generation in 1..=period {
+                    game.next();
+                    let current = cells(game.grid());
+                    assert_eq!(5, current.len());
+                    assert_eq!(generation == period, start == current);
+                }
+            }
+            /// Tests a diagonal pair of cells births the other diagonal
+            /// under a rule birthing two edge neighbors (`2e`),
+            /// but dies out when only a corner next to an edge (`2a`) births.
+            #[test]
+            fn next_hensel_test(x in 0..WIDTH-1, y in 0..HEIGHT-1) {
+                for (rule, born) in [("B2e/S", true), ("B2a/S", false)] {
+                    let mut game = GameOfLife::<TestGrid>::new(rule.parse().unwrap()).unwrap();
+                    game.grid_mut().set(x, y, Cell::Alive).unwrap();
+                    game.grid_mut().set(x + 1, y + 1, Cell::Alive).unwrap();
+                    game.grid_mut().update();
+                    game.next();
+                    let expected = if born { vec![(x + 1, y), (x, y + 1)] } else { vec![] };
+                    assert_eq!(expected, cells(game.grid()));
+                }
             }
         }
-        #[test]
-        fn keep_grid_test(x in 0..WIDTH, y in 0..HEIGHT) {
-            let mut grid = GameOfLife::<TestGrid>::default();
-            let r = grid.grid.set(x, y, Cell::Alive);
-            grid.grid.update();
-            assert!(r.is_ok());
-            let r = grid.grid().get(x, y);
-            assert!(r.is_ok());
-            let r = r.unwrap();
-            let expected = grid.grid.get(x, y);
-            assert!(expected.is_ok());
-            assert_eq!(expected.unwrap(), r);
-        }
-        #[test]
-        fn keep_grid_mut_test(x in 0..WIDTH, y in 0..HEIGHT) {
-            let mut grid = GameOfLife::<TestGrid>::default();
-            let r = grid.grid.set(x, y, Cell::Alive);
-            grid.grid.update();
-            assert!(r.is_ok());
-            let r = grid.grid_mut().get(x, y);
-            assert!(r.is_ok());
-            let r = r.unwrap();
-            let expected = grid.grid.get(x, y);
-            assert!(expected.is_ok());
-            assert_eq!(expected.unwrap(), r);
+    }}
+    #[test]
+    fn rule_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        assert_eq!(&Rule::CONWAY, game.rule());
+        game.set_rule(Rule::MAZE).unwrap();
+        assert_eq!(&Rule::MAZE, game.rule());
+        let game = GameOfLife::from_grid(DynGrid::new(3, 3).unwrap()).with_rule(Rule::SEEDS).unwrap();
+        assert_eq!(&Rule::SEEDS, game.rule());
+    }
+    #[test]
+    fn neighborhood_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        assert_eq!(&Neighborhood::Moore, game.neighborhood());
+        game.set_neighborhood(Neighborhood::Hexagonal).unwrap();
+        assert_eq!(&Neighborhood::Hexagonal, game.neighborhood());
+        let game = GameOfLife::<TestGrid>::default().with_neighborhood(Neighborhood::ExtendedMoore(2)).unwrap();
+        assert_eq!(&Neighborhood::ExtendedMoore(2), game.neighborhood());
+    }
+    #[test]
+    fn topology_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        assert_eq!(Topology::Bounded, game.topology());
+        game.set_topology(Topology::Torus);
+        assert_eq!(Topology::Torus, game.topology());
+        let game = GameOfLife::<TestGrid>::default().with_topology(Topology::KleinBottle);
+        assert_eq!(Topology::KleinBottle, game.topology());
+    }
+    /// Tests a game on an unbounded grid lets a glider
+    /// travel past where the edges of a bounded grid would be.
+    #[test]
+    fn unbounded_glider_test() {
+        let mut game = GameOfLife::<SparseGrid>::default();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            game.grid_mut().set(x - 20, y - 20, Cell::Alive);
+        }
+        game.grid_mut().update();
+        game.step(4 * 40);
+        assert_eq!(5, game.grid().population());
+        let bounds = game.grid().bounding_box().unwrap();
+        assert_eq!((20, 20), (bounds.min_x, bounds.min_y));
+    }
+    /// Tests a blinker oscillates on a grid sized at runtime
+    /// which is wider than it is tall.
+    #[test]
+    fn from_grid_blinker_test() {
+        let mut grid = DynGrid::new(7, 3).unwrap();
+        for x in 2..=4 {
+            grid.set(x, 1, Cell::Alive).unwrap();
+        }
+        grid.update();
+        let mut game = GameOfLife::from_grid(grid);
+        game.next();
+        for y in 0..3 {
+            for x in 0..7 {
+                let expected = if x == 3 { Cell::Alive } else { Cell::Dead };
+                assert_eq!(expected, game.grid().get(x, y).unwrap());
+            }
+        }
+        game.next();
+        for y in 0..3 {
+            for x in 0..7 {
+                let expected = if y == 1 && (2..=4).contains(&x) { Cell::Alive } else { Cell::Dead };
+                assert_eq!(expected, game.grid().get(x, y).unwrap());
+            }
+        }
+    }
+    #[test]
+    fn step_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        assert_eq!(0, game.generation());
+        game.next();
+        assert_eq!(1, game.generation());
+        game.step(10);
+        assert_eq!(11, game.generation());
+        game.step(0);
+        assert_eq!(11, game.generation());
+        game.grid_mut().set(0, 0, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        assert_eq!(11, game.generation());
+    }
+    /// Tests the iterator yields the cells after each generation,
+    /// leaving the game at the last generation taken.
+    #[test]
+    fn generations_test() {
+        let mut game = GameOfLife::from_grid(DynGrid::new(5, 5).unwrap());
+        for x in 1..=3 {
+            game.grid_mut().set(x, 2, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        let horizontal = game.grid().cells();
+        let vertical = vec![((2, 1), Cell::Alive), ((2, 2), Cell::Alive), ((2, 3), Cell::Alive)];
+        let generations: Vec<_> = game.generations().take(4).collect();
+        assert_eq!(4, game.generation());
+        assert_eq!(vec![vertical.clone(), horizontal.clone(), vertical, horizontal], generations);
+    }
+    /// Tests a lone pair of cells dies after one generation,
+    /// while a block never does.
+    #[test]
+    fn run_until_test() {
+        let mut game = GameOfLife::<TestGrid>::default();
+        assert_eq!(Some(0), game.run_until(|grid| grid.population() == 0, 100));
+        game.grid_mut().set(1, 1, Cell::Alive).unwrap();
+        game.grid_mut().set(2, 1, Cell::Alive).unwrap();
+        game.grid_mut().update();
+        assert_eq!(Some(1), game.run_until(|grid| grid.population() == 0, 100));
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+            game.grid_mut().set(x, y, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        assert_eq!(None, game.run_until(|grid| grid.population() == 0, 100));
+        assert_eq!(100, game.generation());
+        assert_eq!(None, game.run_until(|grid| grid.population() == 0, 50));
+        assert_eq!(100, game.generation());
+    }
+
+
+    // Helper Functions
+    // These are called in tests above.
+
+    /// Returns the coordinates of every alive cell in the grid.
+    fn cells(grid: &impl Grid) -> Vec<(usize, usize)> {
+        let mut alive = Vec::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.get(x, y).unwrap() == Cell::Alive {
+                    alive.push((x, y));
+                }
+            }
+        }
+        alive
+    }
+    /// Returns `(i, j)` with `i` along the longer side of a grid of the given size and `j` across it,
+    /// so cells laid out along `i` fit in a single row or column.
+    fn along(width: usize, height: usize, i: usize, j: usize) -> (usize, usize) {
+        if width >= height { (i, j) } else { (j, i) }
+    }
+    /// Returns the positions either side of `j` which are from 0 up to `size`.
+    fn beside(j: usize, size: usize) -> impl Iterator<Item = usize> {
+        [j.checked_sub(1), Some(j + 1).filter(|&j| j < size)].into_iter().flatten()
+    }
+    /// Returns the least common multiple of `a` and `b`.
+    fn lcm(a: usize, b: usize) -> usize {
+        let (mut x, mut y) = (a, b);
+        while y != 0 {
+            (x, y) = (y, x % y);
         }
+        a / x * b
     }
 }
@@ -0,0 +1,357 @@
+//! Block cellular automata in the Margolus neighborhood.
+//!
+//! The grid is split into blocks of 2x2 cells, and each generation every block
+//! is replaced by the block its [`BlockRule`] maps it to, without looking at any other block.
+//! The partition shifts by one cell down and to the right every other generation,
+//! so what happens in one block spreads into its neighbors.
+//! Rules which map each block to a different block are reversible:
+//! the grid can be run backwards with the inverse rule, the partitions in reverse order.
+
+use std::fmt;
+
+use crate::grid::{topology::Topology, Cell, Grid};
+
+/// The cells of a block as `(dx, dy)` offsets from its top left cell,
+/// where the cell at index `i` is bit `i` of the block.
+const BLOCK: [(isize, isize); 4] = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+/// A rule mapping each of the 16 blocks of 2x2 cells to the block it becomes.
+/// A block is numbered by its alive cells, 1 for the top left, 2 for the top right,
+/// 4 for the bottom left and 8 for the bottom right.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct BlockRule {
+    table: [u8; 16],
+}
+
+impl BlockRule {
+    /// Critters: blocks with two alive cells stay as they are,
+    /// every other block is flipped, and blocks which had three alive cells are turned around as well.
+    pub const CRITTERS: BlockRule = BlockRule::critters();
+    /// Tron: blocks of cells all in the same state are flipped, every other block stays as it is.
+    pub const TRON: BlockRule = BlockRule::tron();
+    /// The billiard ball model: a lone cell moves to the opposite corner of its block,
+    /// two cells in opposite corners move to the other two corners, and every other block stays as it is.
+    pub const BILLIARD_BALL: BlockRule = BlockRule::billiard_ball();
+
+    /// Creates a rule where block `n` becomes block `table[n]`.
+    /// Returns None if a block is above 15.
+    pub fn new(table: [u8; 16]) -> Option<Self> {
+        table.iter().all(|&block| block < 16).then_some(BlockRule { table })
+    }
+    const fn critters() -> Self {
+        let mut table = [0; 16];
+        let mut block = 0;
+        while block < 16 {
+            table[block] = match block.count_ones() {
+                2 => block as u8,
+                // Turning the block around swaps the top left with the bottom right,
+                // and the top right with the bottom left, which reverses its bits.
+                3 => (!block as u8 & 0b1111).reverse_bits() >> 4,
+                _ => !block as u8 & 0b1111,
+            };
+            block += 1;
+        }
+        BlockRule { table }
+    }
+    const fn tron() -> Self {
+        let mut table = [0; 16];
+        let mut block = 0;
+        while block < 16 {
+            table[block] = match block {
+                0b0000 => 0b1111,
+                0b1111 => 0b0000,
+                _ => block as u8,
+            };
+            block += 1;
+        }
+        BlockRule { table }
+    }
+    const fn billiard_ball() -> Self {
+        let mut table = [0; 16];
+        let mut block = 0;
+        while block < 16 {
+            table[block] = match block {
+                // The opposite corner of a lone cell is the one with its bits reversed.
+                0b0001 | 0b0010 | 0b0100 | 0b1000 => (block as u8).reverse_bits() >> 4,
+                0b1001 => 0b0110,
+                0b0110 => 0b1001,
+                _ => block as u8,
+            };
+            block += 1;
+        }
+        BlockRule { table }
+    }
+    /// Returns the block `block` becomes, of which only the lowest four bits are used.
+    pub fn next_block(&self, block: u8) -> u8 {
+        self.table[usize::from(block & 0b1111)]
+    }
+    /// Returns true if no two blocks become the same block,
+    /// so every grid can be run backwards.
+    pub fn is_reversible(&self) -> bool {
+        self.inverse().is_some()
+    }
+    /// Returns the rule which turns each block back into the block it came from,
+    /// or None if the rule is not [reversible](BlockRule::is_reversible).
+    pub fn inverse(&self) -> Option<BlockRule> {
+        let mut table = [None; 16];
+        for (block, &next) in (0..).zip(self.table.iter()) {
+            if table[usize::from(next)].replace(block).is_some() {
+                return None;
+            }
+        }
+        // Each of the 16 blocks came from a different block, so every block was reached.
+        Some(BlockRule { table: table.map(|block| block.unwrap()) })
+    }
+}
+
+/// Steps a grid through generations of a [`BlockRule`],
+/// keeping track of where the next partition of blocks begins.
+///
+/// Where the edges of a grid wrap, such as on a [torus](Topology::Torus), the side they join must be even
+/// for every cell to be in exactly one block, so grids with an odd side which wraps are refused. On edges which do not wrap,
+/// the cells past the edge of blocks hanging off the grid are dead,
+/// and whatever the rule moves into them is lost, so those grids may not run backwards.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Margolus {
+    rule: BlockRule,
+    offset: usize,
+}
+
+impl Margolus {
+    pub fn new(rule: BlockRule) -> Self {
+        Margolus { rule, offset: 0 }
+    }
+    pub fn rule(&self) -> &BlockRule {
+        &self.rule
+    }
+    /// Returns how far down and to the right of the top left corner
+    /// the blocks of the next generation begin, `0` or `1`.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    /// Replaces every block of the grid by the block the rule maps it to,
+    /// then shifts the partition for the next generation.
+    ///
+    /// Returns Err without changing the grid if a side of the grid which wraps is odd.
+    pub fn next(&mut self, grid: &mut impl Grid, topology: Topology) -> Result<(), OddSizeError> {
+        check_size(grid, topology)?;
+        apply(&self.rule, grid, topology, self.offset);
+        self.offset ^= 1;
+        Ok(())
+    }
+    /// Undoes the last generation by shifting the partition back
+    /// and replacing every block with the block it came from.
+    ///
+    /// Returns false without changing the grid if the rule is not reversible,
+    /// or Err if a side of the grid which wraps is odd.
+    pub fn previous(&mut self, grid: &mut impl Grid, topology: Topology) -> Result<bool, OddSizeError> {
+        check_size(grid, topology)?;
+        let Some(inverse) = self.rule.inverse() else {
+            return Ok(false);
+        };
+        self.offset ^= 1;
+        apply(&inverse, grid, topology, self.offset);
+        Ok(true)
+    }
+}
+
+/// Returns Err if the grid has an odd side whose edges the topology joins,
+/// which would leave a block overlapping the blocks at the other end.
+fn check_size(grid: &impl Grid, topology: Topology) -> Result<(), OddSizeError> {
+    let (width, height) = (grid.width(), grid.height());
+    if width == 0 || height == 0 {
+        return Ok(());
+    }
+    let wraps = |dx, dy| topology.offset(0, 0, dx, dy, width, height).is_some();
+    if (wraps(-1, 0) && width % 2 == 1) || (wraps(0, -1) && height % 2 == 1) {
+        return Err(OddSizeError::new(topology, width, height));
+    }
+    Ok(())
+}
+
+/// Replaces every block of the partition starting `offset` cells down and to the right
+/// of the top left corner with the block the rule maps it to.
+fn apply(rule: &BlockRule, grid: &mut impl Grid, topology: Topology, offset: usize) {
+    let (width, height) = (grid.width(), grid.height());
+    if width == 0 || height == 0 {
+        return;
+    }
+    // When an edge wraps, the block hanging off the start of the grid is the block at its end,
+    // otherwise it is its own block, starting past the edge.
+    let start = |dx, dy| match topology.offset(0, 0, dx, dy, width, height) {
+        Some(_) => offset as isize,
+        None => -(offset as isize),
+    };
+    let (start_x, start_y) = (start(-1, 0), start(0, -1));
+    for by in (start_y..height as isize).step_by(2) {
+        for bx in (start_x..width as isize).step_by(2) {
+            let cells = BLOCK.map(|(dx, dy)| topology.offset(0, 0, bx + dx, by + dy, width, height));
+            let block = cells.iter().enumerate().fold(0, |block, (i, cell)| {
+                let alive = cell.is_some_and(|(x, y)| grid.get(x, y) == Ok(Cell::Alive));
+                block | u8::from(alive) << i
+            });
+            let next = rule.next_block(block);
+            for (i, cell) in cells.iter().enumerate() {
+                if let Some((x, y)) = *cell {
+                    let cell = if next >> i & 1 == 1 { Cell::Alive } else { Cell::Dead };
+                    // We know the cell is in bounds because the topology placed it on the grid.
+                    grid.set(x, y, cell).unwrap();
+                }
+            }
+        }
+    }
+    grid.update();
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct OddSizeError {
+    topology: Topology,
+    width: usize,
+    height: usize,
+}
+
+impl OddSizeError {
+    pub(crate) fn new(topology: Topology, width: usize, height: usize) -> Self {
+        OddSizeError { topology, width, height }
+    }
+    pub fn topology(&self) -> Topology { self.topology }
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+}
+impl std::error::Error for OddSizeError {}
+impl fmt::Display for OddSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a {}x{} grid can not be split into 2x2 blocks where its edges wrap", self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{grid::dynamic::DynGrid, Universe};
+
+    #[test]
+    fn new_test() {
+        assert_eq!(None, BlockRule::new([16; 16]));
+        let identity: [u8; 16] = std::array::from_fn(|block| block as u8);
+        assert_eq!(Some(BlockRule { table: identity }), BlockRule::new(identity));
+    }
+    #[test]
+    fn reversible_test() {
+        assert!(BlockRule::CRITTERS.is_reversible());
+        assert!(BlockRule::TRON.is_reversible());
+        assert!(BlockRule::BILLIARD_BALL.is_reversible());
+        assert!(!BlockRule::new([0; 16]).unwrap().is_reversible());
+        assert_eq!(Some(BlockRule::TRON), BlockRule::TRON.inverse());
+        assert_eq!(Some(BlockRule::BILLIARD_BALL), BlockRule::BILLIARD_BALL.inverse());
+        assert_ne!(Some(BlockRule::CRITTERS), BlockRule::CRITTERS.inverse());
+    }
+    #[test]
+    fn critters_test() {
+        let rule = BlockRule::CRITTERS;
+        assert_eq!(0b1111, rule.next_block(0b0000));
+        assert_eq!(0b0110, rule.next_block(0b0110));
+        assert_eq!(0b1110, rule.next_block(0b0001));
+        // The dead top right cell is flipped alive, then turned to the bottom left.
+        assert_eq!(0b0100, rule.next_block(0b1101));
+        assert_eq!(0b0000, rule.next_block(0b1111));
+    }
+    /// Tests a lone cell travels one cell diagonally each generation across a torus.
+    #[test]
+    fn billiard_ball_travels_test() {
+        let mut grid: DynGrid = DynGrid::new(8, 8).unwrap();
+        grid.set(0, 0, Cell::Alive).unwrap();
+        grid.update();
+        let mut margolus = Margolus::new(BlockRule::BILLIARD_BALL);
+        for generation in 1..=10 {
+            margolus.next(&mut grid, Topology::Torus).unwrap();
+            assert_eq!(vec![((generation % 8, generation % 8), Cell::Alive)], grid.cells());
+        }
+    }
+    /// Tests Tron flips an empty grid alive, including the blocks hanging off a bounded grid,
+    /// then only flips back the blocks which are entirely on the grid,
+    /// as the dead cells past the edge keep the others from being all the same.
+    #[test]
+    fn tron_test() {
+        let mut grid: DynGrid = DynGrid::new(5, 3).unwrap();
+        let mut margolus = Margolus::new(BlockRule::TRON);
+        margolus.next(&mut grid, Topology::Bounded).unwrap();
+        assert_eq!(15, grid.population());
+        assert_eq!(1, margolus.offset());
+        margolus.next(&mut grid, Topology::Bounded).unwrap();
+        // The top row and the left column are in blocks hanging off the grid.
+        assert_eq!(7, grid.population());
+        assert_eq!(0, margolus.offset());
+    }
+    #[test]
+    fn irreversible_previous_test() {
+        let mut grid: DynGrid = DynGrid::new(4, 4).unwrap();
+        let mut margolus = Margolus::new(BlockRule::new([0; 16]).unwrap());
+        margolus.next(&mut grid, Topology::Torus).unwrap();
+        assert_eq!(Ok(false), margolus.previous(&mut grid, Topology::Torus));
+        assert_eq!(1, margolus.offset());
+    }
+    /// Tests grids with an odd side are refused only where that side's edges wrap,
+    /// leaving the grid and the partition as they were.
+    #[test]
+    fn odd_size_test() {
+        let mut grid: DynGrid = DynGrid::new(5, 4).unwrap();
+        grid.set(0, 0, Cell::Alive).unwrap();
+        grid.update();
+        let mut margolus = Margolus::new(BlockRule::BILLIARD_BALL);
+        for topology in [Topology::Torus, Topology::Cylinder, Topology::KleinBottle, Topology::CrossSurface] {
+            assert_eq!(Err(OddSizeError::new(topology, 5, 4)), margolus.next(&mut grid, topology));
+            assert_eq!(Err(OddSizeError::new(topology, 5, 4)), margolus.previous(&mut grid, topology));
+        }
+        assert_eq!(vec![((0, 0), Cell::Alive)], grid.cells());
+        assert_eq!(0, margolus.offset());
+        assert_eq!(Ok(()), margolus.next(&mut grid, Topology::Bounded));
+        let mut grid: DynGrid = DynGrid::new(4, 5).unwrap();
+        assert_eq!(Ok(()), margolus.next(&mut grid, Topology::Cylinder));
+        assert_eq!(Err(OddSizeError::new(Topology::KleinBottle, 4, 5)), margolus.next(&mut grid, Topology::KleinBottle));
+        let error = OddSizeError::new(Topology::Torus, 4, 5);
+        assert_eq!("a 4x5 grid can not be split into 2x2 blocks where its edges wrap", error.to_string());
+    }
+    proptest! {
+        /// Tests running a reversible rule forward then backward
+        /// restores the original grid.
+        #[test]
+        fn reversible_round_trip_test(
+            cells in prop::collection::vec((0usize..10, 0usize..8), 0..40),
+            rule in prop_oneof![
+                Just(BlockRule::CRITTERS),
+                Just(BlockRule::TRON),
+                Just(BlockRule::BILLIARD_BALL),
+                Just((0..16).collect::<Vec<u8>>()).prop_shuffle().prop_map(|table| BlockRule::new(table.try_into().unwrap()).unwrap()),
+            ],
+            generations in 1usize..30,
+        ) {
+            let mut grid: DynGrid = DynGrid::new(10, 8).unwrap();
+            for (x, y) in cells {
+                grid.set(x, y, Cell::Alive).unwrap();
+            }
+            grid.update();
+            let original = grid.cells();
+            let mut margolus = Margolus::new(rule);
+            for _ in 0..generations {
+                margolus.next(&mut grid, Topology::Torus).unwrap();
+            }
+            for _ in 0..generations {
+                assert_eq!(Ok(true), margolus.previous(&mut grid, Topology::Torus));
+            }
+            assert_eq!(original, grid.cells());
+            assert_eq!(0, margolus.offset());
+        }
+        /// Tests the inverse of a reversible rule turns every block back.
+        #[test]
+        fn inverse_test(table in Just((0..16).collect::<Vec<u8>>()).prop_shuffle()) {
+            let rule = BlockRule::new(table.try_into().unwrap()).unwrap();
+            let inverse = rule.inverse().unwrap();
+            for block in 0..16 {
+                assert_eq!(block, inverse.next_block(rule.next_block(block)));
+            }
+            assert_eq!(Some(rule), inverse.inverse());
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! The Life 1.05 and Life 1.06 pattern formats.
+//!
+//! Life 1.06 lists the coordinates of each alive cell, one per line.
+//! ```text
+//! #Life 1.06
+//! 0 -1
+//! 1 0
+//! -1 1
+//! 0 1
+//! 1 1
+//! ```
+//!
+//! Life 1.05 is made of blocks of rows, each placed by a `#P x y` line,
+//! where `.` is a dead cell and `*` an alive cell.
+//! ```text
+//! #Life 1.05
+//! #D The smallest spaceship.
+//! #N
+//! #P -1 -1
+//! .*.
+//! ..*
+//! ***
+//! ```
+//!
+//! Coordinates in both formats may be negative, so patterns are moved
+//! to have their top left alive cell at the corner of the pattern when parsed.
+
+use std::fmt;
+
+use crate::rule::Rule;
+
+use super::Pattern;
+
+const LIFE_105_HEADER: &str = "#Life 1.05";
+const LIFE_106_HEADER: &str = "#Life 1.06";
+
+/// Parses a pattern from Life 1.06 text.
+pub fn parse_106(text: &str) -> Result<Pattern, ParseLifeError> {
+    let mut lines = numbered_lines(text);
+    expect_header(&mut lines, LIFE_106_HEADER)?;
+    let mut cells = Vec::new();
+    for (line_number, line) in lines {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut coordinates = line.split_whitespace();
+        let mut coordinate = || {
+            coordinates
+                .next()
+                .and_then(|c| c.parse::<i64>().ok())
+                .ok_or(ParseLifeError::new(line_number, ParseLifeErrorKind::InvalidCoordinates))
+        };
+        let cell = (coordinate()?, coordinate()?);
+        if coordinates.next().is_some() {
+            return Err(ParseLifeError::new(line_number, ParseLifeErrorKind::InvalidCoordinates));
+        }
+        cells.push((line_number, cell));
+    }
+    normalize(Pattern::default(), cells)
+}
+
+/// Writes the coordinates of each alive cell of the pattern as Life 1.06 text.
+/// Life 1.06 has no place for a name, comments nor rule, so they are left out.
+pub fn write_106(pattern: &Pattern) -> String {
+    let mut text = format!("{LIFE_106_HEADER}\n");
+    for (x, y) in pattern.cells() {
+        text += &format!("{x} {y}\n");
+    }
+    text
+}
+
+/// Parses a pattern from Life 1.05 text.
+pub fn parse_105(text: &str) -> Result<Pattern, ParseLifeError> {
+    let mut lines = numbered_lines(text);
+    expect_header(&mut lines, LIFE_105_HEADER)?;
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+    // Rows are only allowed after a `#P` line places them.
+    let mut block: Option<(i64, i64)> = None;
+    for (line_number, line) in lines {
+        let error = |kind| Err(ParseLifeError::new(line_number, kind));
+        if let Some(description) = line.strip_prefix("#D") {
+            pattern.comments.push(description.trim().to_string());
+        } else if line == "#N" {
+            pattern.rule = Some(Rule::CONWAY);
+        } else if let Some(rule) = line.strip_prefix("#R") {
+            match rule.trim().parse() {
+                Ok(rule) => pattern.rule = Some(rule),
+                Err(_) => return error(ParseLifeErrorKind::InvalidRule),
+            }
+        } else if let Some(position) = line.strip_prefix("#P") {
+            let position: Vec<_> = position.split_whitespace().map(str::parse::<i64>).collect();
+            match position[..] {
+                [Ok(x), Ok(y)] => block = Some((x, y)),
+                _ => return error(ParseLifeErrorKind::InvalidCoordinates),
+            }
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else if let Some((x, y)) = &mut block {
+            for (dx, c) in line.chars().enumerate() {
+                match c {
+                    '.' => {},
+                    '*' => match i64::try_from(dx).ok().and_then(|dx| x.checked_add(dx)) {
+                        Some(x) => cells.push((line_number, (x, *y))),
+                        None => return error(ParseLifeErrorKind::TooLarge),
+                    },
+                    c => return error(ParseLifeErrorKind::InvalidCharacter(c)),
+                }
+            }
+            match y.checked_add(1) {
+                Some(next) => *y = next,
+                None => return error(ParseLifeErrorKind::TooLarge),
+            }
+        } else {
+            return error(ParseLifeErrorKind::MissingPosition);
+        }
+    }
+    normalize(pattern, cells)
+}
+
+/// Writes the pattern as Life 1.05 text, in a single block centered on the origin.
+/// Life 1.05 has no place for a name, so it is left out.
+pub fn write_105(pattern: &Pattern) -> String {
+    let mut text = format!("{LIFE_105_HEADER}\n");
+    for comment in &pattern.comments {
+        text += &format!("#D {comment}\n");
+    }
+    match pattern.rule {
+        Some(Rule::CONWAY) | None => text += "#N\n",
+        // Life 1.05 writes rules in the older `S/B` notation.
+        Some(rule) => text += &format!("#R {}\n", rule.to_survival_birth_string()),
+    }
+    let x = -((pattern.width() / 2) as i64);
+    let y = -((pattern.height() / 2) as i64);
+    text += &format!("#P {x} {y}\n");
+    for row in pattern.rows() {
+        let mut line = vec!['.'; row.last().map_or(0, |&x| x + 1)];
+        for x in row {
+            line[x] = '*';
+        }
+        if line.is_empty() {
+            // A line with no cells would be skipped as empty,
+            // so write a single dead cell to keep the row.
+            line.push('.');
+        }
+        text.extend(line);
+        text.push('\n');
+    }
+    text
+}
+
+fn numbered_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines().enumerate().map(|(i, line)| (i + 1, line.trim()))
+}
+
+fn expect_header<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>, header: &str) -> Result<(), ParseLifeError> {
+    match lines.next() {
+        Some((_, line)) if line == header => Ok(()),
+        _ => Err(ParseLifeError::new(1, ParseLifeErrorKind::MissingHeader)),
+    }
+}
+
+/// Moves the cells, each given with the line it was on,
+/// so the top left alive cell is at the corner of the pattern.
+/// Returns Err at the first cell too far from the top left to fit in a pattern.
+fn normalize(pattern: Pattern, cells: Vec<(usize, (i64, i64))>) -> Result<Pattern, ParseLifeError> {
+    let (min_x, min_y) = cells.iter().fold((i64::MAX, i64::MAX), |(min_x, min_y), &(_, (x, y))| (min_x.min(x), min_y.min(y)));
+    // The size must also fit, which is one more than the furthest cell.
+    let distance = |a: i64, min: i64| usize::try_from(a.abs_diff(min)).ok().filter(|&d| d < usize::MAX);
+    let mut moved = Vec::with_capacity(cells.len());
+    for (line_number, (x, y)) in cells {
+        match (distance(x, min_x), distance(y, min_y)) {
+            (Some(x), Some(y)) => moved.push((x, y)),
+            _ => return Err(ParseLifeError::new(line_number, ParseLifeErrorKind::TooLarge)),
+        }
+    }
+    let width = moved.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let height = moved.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+    // We know every cell is within the size,
+    // because the size was made to fit the cells.
+    let Pattern { name, author, comments, rule, .. } = pattern;
+    Ok(Pattern { name, author, comments, rule, ..Pattern::new(width, height, moved).unwrap() })
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParseLifeError {
+    line: usize,
+    kind: ParseLifeErrorKind,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ParseLifeErrorKind {
+    /// The first line is not `#Life 1.05` or `#Life 1.06`.
+    MissingHeader,
+    /// A line which is not two whole numbers.
+    InvalidCoordinates,
+    /// The `#R` line could not be parsed.
+    InvalidRule,
+    /// A character which is not `.` or `*` in a row.
+    InvalidCharacter(char),
+    /// A row before any `#P` line.
+    MissingPosition,
+    /// A cell too far from the others for the pattern's size to fit in a `usize`.
+    TooLarge,
+}
+
+impl ParseLifeError {
+    pub(crate) fn new(line: usize, kind: ParseLifeErrorKind) -> Self {
+        ParseLifeError { line, kind }
+    }
+    /// Returns the line of the error, starting at 1.
+    pub fn line(&self) -> usize { self.line }
+    pub fn kind(&self) -> ParseLifeErrorKind { self.kind }
+}
+impl std::error::Error for ParseLifeError {}
+impl fmt::Display for ParseLifeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ParseLifeErrorKind::MissingHeader => write!(f, "missing '#Life' header"),
+            ParseLifeErrorKind::InvalidCoordinates => write!(f, "invalid coordinates"),
+            ParseLifeErrorKind::InvalidRule => write!(f, "invalid rule"),
+            ParseLifeErrorKind::InvalidCharacter(c) => write!(f, "unexpected character '{c}'"),
+            ParseLifeErrorKind::MissingPosition => write!(f, "row before a '#P' position"),
+            ParseLifeErrorKind::TooLarge => write!(f, "cell too far from the rest of the pattern"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{
+        grid::{obj::GameOfLifeGrid, Cell, Grid},
+        pattern::plaintext,
+    };
+
+    const GLIDER_CELLS: [(usize, usize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+    #[test]
+    fn parse_106_test() {
+        let pattern = parse_106("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!(3, pattern.width());
+        assert_eq!(3, pattern.height());
+        assert_eq!(&GLIDER_CELLS, pattern.cells());
+    }
+    #[test]
+    fn write_106_test() {
+        let pattern = Pattern::new(3, 3, GLIDER_CELLS).unwrap();
+        assert_eq!("#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n", write_106(&pattern));
+    }
+    #[test]
+    fn parse_106_error_test() {
+        let kind = |text: &str| parse_106(text).unwrap_err();
+        assert_eq!(ParseLifeError::new(1, ParseLifeErrorKind::MissingHeader), kind("0 0\n"));
+        assert_eq!(ParseLifeError::new(1, ParseLifeErrorKind::MissingHeader), kind("#Life 1.05\n"));
+        assert_eq!(ParseLifeError::new(3, ParseLifeErrorKind::InvalidCoordinates), kind("#Life 1.06\n0 0\n1\n"));
+        assert_eq!(ParseLifeError::new(2, ParseLifeErrorKind::InvalidCoordinates), kind("#Life 1.06\n0 0 0\n"));
+        assert_eq!(ParseLifeError::new(2, ParseLifeErrorKind::InvalidCoordinates), kind("#Life 1.06\na 0\n"));
+    }
+    /// Tests cells at the far ends of the coordinates are reported
+    /// rather than overflowing the size of the pattern.
+    #[test]
+    fn parse_106_too_large_test() {
+        let text = format!("#Life 1.06\n{} 0\n{} 0\n", i64::MIN, i64::MAX);
+        assert_eq!(Err(ParseLifeError::new(3, ParseLifeErrorKind::TooLarge)), parse_106(&text));
+        let text = format!("#Life 1.06\n0 {}\n0 {}\n", i64::MAX, i64::MIN);
+        assert_eq!(Err(ParseLifeError::new(2, ParseLifeErrorKind::TooLarge)), parse_106(&text));
+        let text = format!("#Life 1.06\n{} 0\n{} 0\n", i64::MIN + 1, i64::MAX);
+        let pattern = parse_106(&text).unwrap();
+        assert_eq!(usize::MAX, pattern.width());
+        assert_eq!(&[(0, 0), (usize::MAX - 1, 0)], pattern.cells());
+    }
+    #[test]
+    fn parse_105_test() {
+        let text = "#Life 1.05\n#D The smallest spaceship.\n#R 23/36\n#P -1 -1\n.*.\n..*\n#P -1 1\n***\n";
+        let pattern = parse_105(text).unwrap();
+        assert_eq!(vec!["The smallest spaceship.".to_string()], pattern.comments);
+        assert_eq!(Some(Rule::HIGHLIFE), pattern.rule);
+        assert_eq!(&GLIDER_CELLS, pattern.cells());
+    }
+    #[test]
+    fn write_105_test() {
+        let mut pattern = Pattern::new(3, 3, GLIDER_CELLS).unwrap();
+        pattern.comments.push("The smallest spaceship.".to_string());
+        assert_eq!("#Life 1.05\n#D The smallest spaceship.\n#N\n#P -1 -1\n.*\n..*\n***\n", write_105(&pattern));
+        pattern.rule = Some(Rule::HIGHLIFE);
+        assert!(write_105(&pattern).contains("\n#R 23/36\n"));
+        pattern.rule = Some(Rule::BRIANS_BRAIN);
+        assert!(write_105(&pattern).contains("\n#R /2/3\n"));
+        pattern.rule = Some(Rule::STAR_WARS);
+        assert!(write_105(&pattern).contains("\n#R 345/2/4\n"));
+    }
+    #[test]
+    fn parse_105_error_test() {
+        let error = |text: &str| parse_105(text).unwrap_err();
+        assert_eq!(ParseLifeError::new(2, ParseLifeErrorKind::MissingPosition), error("#Life 1.05\n.*.\n"));
+        assert_eq!(ParseLifeError::new(2, ParseLifeErrorKind::InvalidCoordinates), error("#Life 1.05\n#P 0\n"));
+        assert_eq!(ParseLifeError::new(3, ParseLifeErrorKind::InvalidCharacter('O')), error("#Life 1.05\n#P 0 0\n.O\n"));
+        assert_eq!(ParseLifeError::new(2, ParseLifeErrorKind::InvalidRule), error("#Life 1.05\n#R 9/3\n"));
+        assert_eq!("line 2: invalid rule", error("#Life 1.05\n#R 9/3\n").to_string());
+        let text = format!("#Life 1.05\n#P {} 0\n.*\n", i64::MAX);
+        assert_eq!(ParseLifeError::new(3, ParseLifeErrorKind::TooLarge), error(&text));
+        let text = format!("#Life 1.05\n#P {} {}\n*\n#P {} 0\n*\n", i64::MIN, i64::MAX - 1, i64::MAX);
+        assert_eq!(ParseLifeError::new(5, ParseLifeErrorKind::TooLarge), error(&text));
+        let text = format!("#Life 1.05\n#P 0 {}\n*\n*\n", i64::MAX);
+        assert_eq!(ParseLifeError::new(3, ParseLifeErrorKind::TooLarge), error(&text));
+    }
+    /// Tests a pattern read from one format is placed at an offset on a grid,
+    /// and can be written out in another format.
+    #[test]
+    fn write_to_grid_test() {
+        let pattern = parse_106("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        let mut grid = GameOfLifeGrid::<8, 8>::default();
+        pattern.write_to(&mut grid, 2, 3).unwrap();
+        for y in 0usize..8 {
+            for x in 0usize..8 {
+                let expected = GLIDER_CELLS.contains(&(x.wrapping_sub(2), y.wrapping_sub(3)));
+                assert_eq!(Ok(if expected { Cell::Alive } else { Cell::Dead }), grid.get(x, y));
+            }
+        }
+        let text = plaintext::write(&Pattern::from_grid(&grid));
+        assert_eq!("........\n........\n........\n...O....\n....O...\n..OOO...\n........\n........\n", text);
+    }
+    proptest! {
+        /// Tests writing then parsing a pattern gives back the same cells,
+        /// once moved to the top left.
+        #[test]
+        fn round_trip_test(cells in proptest::collection::vec((0usize..40, 0usize..20), 1..100)) {
+            let min_x = cells.iter().map(|&(x, _)| x).min().unwrap();
+            let min_y = cells.iter().map(|&(_, y)| y).min().unwrap();
+            let pattern = Pattern::new(40, 20, cells.iter().copied()).unwrap();
+            let expected = Pattern::new(40, 20, cells.iter().map(|&(x, y)| (x - min_x, y - min_y))).unwrap();
+            assert_eq!(expected.cells(), parse_106(&write_106(&pattern)).unwrap().cells());
+            let mut pattern = pattern;
+            pattern.comments.push("A comment".to_string());
+            pattern.rule = Some(Rule::DAY_AND_NIGHT);
+            let parsed = parse_105(&write_105(&pattern)).unwrap();
+            assert_eq!(expected.cells(), parsed.cells());
+            assert_eq!(pattern.comments, parsed.comments);
+            assert_eq!(pattern.rule, parsed.rule);
+        }
+        /// Tests Generations and Hensel rules are written to Life 1.05 in a way they parse back from.
+        #[test]
+        fn rule_round_trip_test(
+            rule in prop_oneof![
+                (0u16..1 << 9, 0u16..1 << 9, 3usize..=255).prop_map(|(birth, survival, states)| {
+                    let counts = |mask: u16| (0..=8).filter(|count| mask & 1 << count != 0).collect::<Vec<_>>();
+                    Rule::generations(&counts(birth), &counts(survival), states).unwrap()
+                }),
+                prop::sample::select(vec!["B2-a/S12", "B3/S2-i34q", "B2ce3-k/S/C3", "B2e3-jn/S23-q/C5", "B2a/S"])
+                    .prop_map(|rule| rule.parse::<Rule>().unwrap()),
+            ],
+        ) {
+            let mut pattern = Pattern::new(3, 3, GLIDER_CELLS).unwrap();
+            pattern.rule = Some(rule);
+            assert_eq!(Some(rule), parse_105(&write_105(&pattern)).unwrap().rule);
+        }
+    }
+}
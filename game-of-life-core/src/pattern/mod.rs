@@ -0,0 +1,136 @@
+use crate::{
+    grid::{Cell, CellOutOfBoundsError, Grid},
+    rule::Rule,
+};
+
+pub mod life;
+pub mod plaintext;
+pub mod rle;
+pub mod soup;
+
+/// A rectangle of cells read from, or to be written to, a pattern file.
+/// Only the alive cells are stored, relative to the top left corner of the rectangle.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    /// The rule the pattern was made for, if the file says so.
+    pub rule: Option<Rule>,
+    width: usize,
+    height: usize,
+    // Sorted by row, then column.
+    cells: Vec<(usize, usize)>,
+}
+
+impl Pattern {
+    /// Creates a pattern of the given size from the coordinates of its alive cells,
+    /// returns Err with the first cell outside of the size.
+    pub fn new(width: usize, height: usize, cells: impl IntoIterator<Item = (usize, usize)>) -> Result<Self, CellOutOfBoundsError> {
+        let mut cells: Vec<_> = cells.into_iter().collect();
+        if let Some(&(x, y)) = cells.iter().find(|&&(x, y)| x >= width || y >= height) {
+            return Err(CellOutOfBoundsError::new(x, y));
+        }
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        Ok(Pattern { width, height, cells, ..Pattern::default() })
+    }
+    /// Creates a pattern from every cell of the grid.
+    pub fn from_grid(grid: &impl Grid) -> Self {
+        let mut cells = Vec::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if grid.get(x, y) == Ok(Cell::Alive) {
+                    cells.push((x, y));
+                }
+            }
+        }
+        Pattern { width: grid.width(), height: grid.height(), cells, ..Pattern::default() }
+    }
+    /// Sets the cells of the grid covered by the pattern, with its top left corner at `(x, y)`,
+    /// then updates the grid.
+    /// Cells of the grid outside of the pattern are left as they are.
+    /// Returns Err without changing the grid if the pattern does not fit.
+    pub fn write_to(&self, grid: &mut impl Grid, x: usize, y: usize) -> Result<(), CellOutOfBoundsError> {
+        if self.width > 0 && self.height > 0 {
+            let right = x.checked_add(self.width - 1).ok_or(CellOutOfBoundsError::new(usize::MAX, y))?;
+            let bottom = y.checked_add(self.height - 1).ok_or(CellOutOfBoundsError::new(x, usize::MAX))?;
+            grid.get(right, bottom)?;
+        }
+        for py in 0..self.height {
+            for px in 0..self.width {
+                grid.set(x + px, y + py, Cell::Dead)?;
+            }
+        }
+        for &(px, py) in &self.cells {
+            grid.set(x + px, y + py, Cell::Alive)?;
+        }
+        grid.update();
+        Ok(())
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns the coordinates of the alive cells, sorted by row, then column.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+    /// Returns the alive cells of each row, by row.
+    fn rows(&self) -> Vec<Vec<usize>> {
+        let mut rows = vec![Vec::new(); self.height];
+        for &(x, y) in &self.cells {
+            rows[y].push(x);
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::tests::*;
+
+    #[test]
+    fn new_test() {
+        let pattern = Pattern::new(3, 2, [(2, 1), (0, 0), (1, 0), (0, 0)]).unwrap();
+        assert_eq!(3, pattern.width());
+        assert_eq!(2, pattern.height());
+        assert_eq!(&[(0, 0), (1, 0), (2, 1)], pattern.cells());
+        assert_eq!(CellOutOfBoundsError::new(3, 0), Pattern::new(3, 2, [(0, 0), (3, 0)]).unwrap_err());
+    }
+    #[test]
+    fn write_to_out_of_bounds_test() {
+        let pattern = Pattern::new(3, 3, [(1, 1)]).unwrap();
+        let mut grid = TestGrid::default();
+        assert_eq!(CellOutOfBoundsError::new(WIDTH, 2), pattern.write_to(&mut grid, WIDTH - 2, 0).unwrap_err());
+        assert_eq!(CellOutOfBoundsError::new(usize::MAX, 0), pattern.write_to(&mut grid, usize::MAX, 0).unwrap_err());
+        assert_eq!(Ok(Cell::Dead), grid.get(WIDTH - 1, 1));
+        assert_eq!(Pattern::from_grid(&TestGrid::default()).cells(), Pattern::from_grid(&grid).cells());
+    }
+    proptest! {
+        /// Tests writing a pattern to a grid, then reading the grid back,
+        /// gives the pattern moved by the offset.
+        #[test]
+        fn write_to_from_grid_test(
+            cells in proptest::collection::vec((0usize..4, 0usize..5), 0..20),
+            x in 0..WIDTH - 4,
+            y in 0..HEIGHT - 5,
+        ) {
+            let pattern = Pattern::new(4, 5, cells).unwrap();
+            let mut grid = TestGrid::default();
+            // Cells covered by the pattern are cleared.
+            grid.set(x, y, Cell::Alive).unwrap();
+            grid.update();
+            pattern.write_to(&mut grid, x, y).unwrap();
+            let read = Pattern::from_grid(&grid);
+            let moved: Vec<_> = pattern.cells().iter().map(|&(px, py)| (px + x, py + y)).collect();
+            assert_eq!(moved, read.cells());
+            assert_eq!(WIDTH, read.width());
+            assert_eq!(HEIGHT, read.height());
+        }
+    }
+}
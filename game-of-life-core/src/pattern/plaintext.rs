@@ -0,0 +1,171 @@
+//! The plaintext (`.cells`) pattern format.
+//!
+//! ```text
+//! !Name: Glider
+//! !The smallest spaceship.
+//! .O.
+//! ..O
+//! OOO
+//! ```
+//!
+//! `.` is a dead cell and `O` an alive cell, one row per line.
+//! Lines starting with `!` are comments.
+
+use std::fmt;
+
+use super::Pattern;
+
+/// Parses a pattern from plaintext.
+/// The pattern is as wide as its longest row.
+pub fn parse(text: &str) -> Result<Pattern, ParsePlaintextError> {
+    let mut pattern = Pattern::default();
+    let mut cells = Vec::new();
+    let mut width = 0;
+    let mut height = 0;
+    for (i, line) in text.lines().enumerate() {
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = Some(author.trim().to_string());
+            } else {
+                pattern.comments.push(comment.trim().to_string());
+            }
+            continue;
+        }
+        let line = line.trim_end();
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '.' => {},
+                'O' | '*' => cells.push((x, height)),
+                c => return Err(ParsePlaintextError::new(i + 1, x + 1, c)),
+            }
+        }
+        width = width.max(line.chars().count());
+        height += 1;
+    }
+    // We know every cell is within the size,
+    // because the size was grown to fit each row as it was read.
+    let Pattern { name, author, comments, .. } = pattern;
+    Ok(Pattern { name, author, comments, ..Pattern::new(width, height, cells).unwrap() })
+}
+
+/// Writes the pattern as plaintext, every row as wide as the pattern.
+/// Plaintext has no place for a rule, so it is left out.
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = String::new();
+    if let Some(name) = &pattern.name {
+        text += &format!("!Name: {name}\n");
+    }
+    if let Some(author) = &pattern.author {
+        text += &format!("!Author: {author}\n");
+    }
+    for comment in &pattern.comments {
+        text += &format!("!{comment}\n");
+    }
+    for row in pattern.rows() {
+        let mut line = vec!['.'; pattern.width];
+        for x in row {
+            line[x] = 'O';
+        }
+        text.extend(line);
+        text.push('\n');
+    }
+    text
+}
+
+/// A character which is not a cell was found in a row.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParsePlaintextError {
+    line: usize,
+    column: usize,
+    character: char,
+}
+
+impl ParsePlaintextError {
+    pub(crate) fn new(line: usize, column: usize, character: char) -> Self {
+        ParsePlaintextError { line, column, character }
+    }
+    /// Returns the line of the error, starting at 1.
+    pub fn line(&self) -> usize { self.line }
+    /// Returns the column of the error, starting at 1.
+    pub fn column(&self) -> usize { self.column }
+    pub fn character(&self) -> char { self.character }
+}
+impl std::error::Error for ParsePlaintextError {}
+impl fmt::Display for ParsePlaintextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: unexpected character '{}'", self.line, self.column, self.character)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{
+        grid::{Cell, Grid},
+        tests::*,
+    };
+
+    const GLIDER: &str = "!Name: Glider
+!Author: Richard K. Guy
+!The smallest, most common, and first discovered spaceship.
+.O.
+..O
+OOO
+";
+
+    #[test]
+    fn parse_glider_test() {
+        let pattern = parse(GLIDER).unwrap();
+        assert_eq!(Some("Glider".to_string()), pattern.name);
+        assert_eq!(Some("Richard K. Guy".to_string()), pattern.author);
+        assert_eq!(vec!["The smallest, most common, and first discovered spaceship.".to_string()], pattern.comments);
+        assert_eq!(3, pattern.width());
+        assert_eq!(3, pattern.height());
+        assert_eq!(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], pattern.cells());
+    }
+    #[test]
+    fn write_glider_test() {
+        assert_eq!(GLIDER, write(&parse(GLIDER).unwrap()));
+    }
+    /// Tests rows may be shorter than the pattern, and empty lines are dead rows.
+    #[test]
+    fn parse_ragged_rows_test() {
+        let pattern = parse("O\n\n...*.\n").unwrap();
+        assert_eq!(5, pattern.width());
+        assert_eq!(3, pattern.height());
+        assert_eq!(&[(0, 0), (3, 2)], pattern.cells());
+    }
+    #[test]
+    fn parse_error_test() {
+        let e = parse("!Comment\n.O.\n.Ox\n").unwrap_err();
+        assert_eq!(ParsePlaintextError::new(3, 3, 'x'), e);
+        assert_eq!("line 3, column 3: unexpected character 'x'", e.to_string());
+    }
+    /// Tests a pattern is placed with its top left corner at the given position.
+    #[test]
+    fn write_to_offset_test() {
+        let mut grid = TestGrid::default();
+        parse(GLIDER).unwrap().write_to(&mut grid, WIDTH - 3, HEIGHT - 3).unwrap();
+        assert_eq!(Ok(Cell::Alive), grid.get(WIDTH - 2, HEIGHT - 3));
+        assert_eq!(Ok(Cell::Alive), grid.get(WIDTH - 1, HEIGHT - 1));
+        assert_eq!(Ok(Cell::Dead), grid.get(WIDTH - 3, HEIGHT - 3));
+        assert!(parse(GLIDER).unwrap().write_to(&mut grid, WIDTH - 2, 0).is_err());
+    }
+    proptest! {
+        /// Tests writing then parsing a pattern gives back the same pattern.
+        #[test]
+        fn round_trip_test(
+            width in 1usize..40,
+            height in 1usize..20,
+            cells in proptest::collection::vec((0usize..40, 0usize..20), 0..100),
+        ) {
+            let cells = cells.into_iter().filter(|&(x, y)| x < width && y < height);
+            let mut pattern = Pattern::new(width, height, cells).unwrap();
+            pattern.comments.push("A comment".to_string());
+            assert_eq!(pattern, parse(&write(&pattern)).unwrap());
+        }
+    }
+}
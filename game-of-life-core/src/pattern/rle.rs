@@ -0,0 +1,392 @@
+//! The Run Length Encoded (RLE) pattern format.
+//!
+//! ```text
+//! #N Glider
+//! #C The smallest spaceship.
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+//!
+//! `b` is a dead cell, `o` an alive cell and `$` the end of a row,
+//! each may be preceded by the number of times it repeats.
+//! The pattern ends with `!`.
+
+use std::fmt;
+
+use crate::rule::{ParseRuleErrorKind, Rule};
+
+use super::Pattern;
+
+/// Lines of written patterns are kept within this many characters.
+const MAX_LINE_LENGTH: usize = 70;
+
+/// Parses a pattern from RLE text.
+pub fn parse(text: &str) -> Result<Pattern, ParseRleError> {
+    let mut pattern = Pattern::default();
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+    // Comments come before the header.
+    let (line_number, header) = loop {
+        match lines.next() {
+            Some((_, line)) if line.trim().is_empty() => {},
+            Some((_, line)) if line.starts_with('#') => parse_comment(line, &mut pattern),
+            Some(header) => break header,
+            None => return Err(ParseRleError::new(text.lines().count() + 1, 1, ParseRleErrorKind::MissingHeader)),
+        }
+    };
+    parse_header(header, line_number, &mut pattern)?;
+    let mut cells = Vec::new();
+    let (mut x, mut y): (usize, usize) = (0, 0);
+    let mut run: Option<usize> = None;
+    for (line_number, line) in lines {
+        // Some files keep writing comments after the header.
+        if line.starts_with('#') {
+            parse_comment(line, &mut pattern);
+            continue;
+        }
+        for (i, c) in line.chars().enumerate() {
+            let error = |kind| Err(ParseRleError::new(line_number, i + 1, kind));
+            match c {
+                '0'..='9' => {
+                    // We know the character is a digit because we just matched it.
+                    let digit = c.to_digit(10).unwrap() as usize;
+                    match run.unwrap_or(0).checked_mul(10).and_then(|run| run.checked_add(digit)) {
+                        Some(count) => run = Some(count),
+                        None => return error(ParseRleErrorKind::InvalidCount),
+                    }
+                },
+                'b' | 'o' => {
+                    let count = run.take().unwrap_or(1);
+                    let end = match x.checked_add(count) {
+                        Some(end) if end <= pattern.width && y < pattern.height => end,
+                        _ => return error(ParseRleErrorKind::OutOfBounds),
+                    };
+                    if c == 'o' {
+                        cells.extend((x..end).map(|x| (x, y)));
+                    }
+                    x = end;
+                },
+                '$' => {
+                    let count = run.take().unwrap_or(1);
+                    y = match y.checked_add(count) {
+                        Some(end) if end <= pattern.height => end,
+                        _ => return error(ParseRleErrorKind::OutOfBounds),
+                    };
+                    x = 0;
+                },
+                '!' => {
+                    if run.is_some() {
+                        return error(ParseRleErrorKind::InvalidCount);
+                    }
+                    // We know every cell is within the size,
+                    // because the size was checked as each run was read.
+                    let Pattern { name, author, comments, rule, .. } = pattern;
+                    let pattern = Pattern::new(pattern.width, pattern.height, cells).unwrap();
+                    return Ok(Pattern { name, author, comments, rule, ..pattern });
+                },
+                c if c.is_whitespace() => {
+                    if run.is_some() {
+                        return error(ParseRleErrorKind::InvalidCount);
+                    }
+                },
+                c => return error(ParseRleErrorKind::InvalidCharacter(c)),
+            }
+        }
+    }
+    Err(ParseRleError::new(text.lines().count() + 1, 1, ParseRleErrorKind::MissingTerminator))
+}
+
+fn parse_comment(line: &str, pattern: &mut Pattern) {
+    let mut chars = line.chars();
+    chars.next();
+    let tag = chars.next();
+    let text = chars.as_str().trim().to_string();
+    match tag {
+        Some('N') => pattern.name = Some(text),
+        Some('O') => pattern.author = Some(text),
+        Some('C' | 'c') => pattern.comments.push(text),
+        // Older files give the rule as a comment.
+        Some('r') => pattern.rule = text.parse().ok().or(pattern.rule),
+        // Other lines, such as the position of the pattern,
+        // have no meaning for a pattern which is placed by the user.
+        _ => {},
+    }
+}
+
+fn parse_header(line: &str, line_number: usize, pattern: &mut Pattern) -> Result<(), ParseRleError> {
+    let (mut width, mut height) = (None, None);
+    let mut column = 1;
+    for field in line.split(',') {
+        let error = |kind| ParseRleError::new(line_number, column, kind);
+        let (key, value) = field.split_once('=').ok_or(error(ParseRleErrorKind::InvalidHeader))?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = Some(value.parse().map_err(|_| error(ParseRleErrorKind::InvalidHeader))?),
+            "y" => height = Some(value.parse().map_err(|_| error(ParseRleErrorKind::InvalidHeader))?),
+            "rule" => {
+                // Golly follows the rule with the grid it runs on, ex. `:T20,20` for a 20 by 20 torus,
+                // whose size has a comma of its own, so nothing after it is another field.
+                let (value, grid) = value.split_once(':').map_or((value, None), |(rule, grid)| (rule.trim(), Some(grid)));
+                let rule = value.parse::<Rule>().map_err(|e| error(ParseRleErrorKind::InvalidRule(e.kind())))?;
+                pattern.rule = Some(rule);
+                if grid.is_some() {
+                    break;
+                }
+            },
+            _ => return Err(error(ParseRleErrorKind::InvalidHeader)),
+        }
+        column += field.chars().count() + 1;
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => {
+            pattern.width = width;
+            pattern.height = height;
+            Ok(())
+        },
+        _ => Err(ParseRleError::new(line_number, 1, ParseRleErrorKind::InvalidHeader)),
+    }
+}
+
+/// Writes the pattern as RLE text.
+pub fn write(pattern: &Pattern) -> String {
+    let mut text = String::new();
+    if let Some(name) = &pattern.name {
+        text += &format!("#N {name}\n");
+    }
+    if let Some(author) = &pattern.author {
+        text += &format!("#O {author}\n");
+    }
+    for comment in &pattern.comments {
+        text += &format!("#C {comment}\n");
+    }
+    text += &format!("x = {}, y = {}, rule = {}\n", pattern.width, pattern.height, pattern.rule.unwrap_or_default());
+    let mut runs = Vec::new();
+    let mut pending_rows = 0;
+    for row in pattern.rows() {
+        if row.is_empty() {
+            pending_rows += 1;
+            continue;
+        }
+        if !runs.is_empty() || pending_rows > 0 {
+            runs.push(run(pending_rows + usize::from(!runs.is_empty()), '$'));
+        }
+        pending_rows = 0;
+        let mut x = 0;
+        let mut i = 0;
+        while i < row.len() {
+            let start = row[i];
+            let mut end = start + 1;
+            while i + 1 < row.len() && row[i + 1] == end {
+                i += 1;
+                end += 1;
+            }
+            if start > x {
+                runs.push(run(start - x, 'b'));
+            }
+            runs.push(run(end - start, 'o'));
+            x = end;
+            i += 1;
+        }
+    }
+    runs.push("!".to_string());
+    let mut line_length = 0;
+    for run in runs {
+        if line_length + run.len() > MAX_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        }
+        line_length += run.len();
+        text += &run;
+    }
+    text.push('\n');
+    text
+}
+
+fn run(count: usize, tag: char) -> String {
+    if count == 1 { tag.to_string() } else { format!("{count}{tag}") }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParseRleError {
+    line: usize,
+    column: usize,
+    kind: ParseRleErrorKind,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ParseRleErrorKind {
+    /// There is no `x = .., y = ..` line.
+    MissingHeader,
+    /// The `x = .., y = ..` line could not be read.
+    InvalidHeader,
+    /// The rule of the header could not be parsed.
+    InvalidRule(ParseRuleErrorKind),
+    /// A character which is not a count, `b`, `o`, `$` or `!`.
+    InvalidCharacter(char),
+    /// A count which is too large, or is not followed by what it counts.
+    InvalidCount,
+    /// A cell past the size given by the header.
+    OutOfBounds,
+    /// The pattern does not end with `!`.
+    MissingTerminator,
+}
+
+impl ParseRleError {
+    pub(crate) fn new(line: usize, column: usize, kind: ParseRleErrorKind) -> Self {
+        ParseRleError { line, column, kind }
+    }
+    /// Returns the line of the error, starting at 1.
+    pub fn line(&self) -> usize { self.line }
+    /// Returns the column of the error, starting at 1.
+    pub fn column(&self) -> usize { self.column }
+    pub fn kind(&self) -> ParseRleErrorKind { self.kind }
+}
+impl std::error::Error for ParseRleError {}
+impl fmt::Display for ParseRleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            ParseRleErrorKind::MissingHeader => write!(f, "missing 'x = .., y = ..' header"),
+            ParseRleErrorKind::InvalidHeader => write!(f, "invalid 'x = .., y = ..' header"),
+            ParseRleErrorKind::InvalidRule(kind) => write!(f, "invalid rule, {}", crate::rule::ParseRuleError::new(kind)),
+            ParseRleErrorKind::InvalidCharacter(c) => write!(f, "unexpected character '{c}'"),
+            ParseRleErrorKind::InvalidCount => write!(f, "invalid run count"),
+            ParseRleErrorKind::OutOfBounds => write!(f, "cells past the size given by the header"),
+            ParseRleErrorKind::MissingTerminator => write!(f, "missing '!' at the end of the pattern"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{
+        grid::{Cell, Grid},
+        tests::*,
+    };
+
+    const GLIDER: &str = "#N Glider
+#O Richard K. Guy
+#C The smallest, most common, and first discovered spaceship.
+#C www.conwaylife.com/wiki/index.php?title=Glider
+x = 3, y = 3, rule = B3/S23
+bo$2bo$3o!
+";
+    const TORUS_GLIDER: &str = "#N Glider on a torus
+x = 3, y = 3, rule = B3/S23:T20,20
+bo$2bo$3o!
+";
+    const LATE_COMMENTS: &str = "x = 3, y = 1, rule = B3/S23
+#N Blinker
+#C The smallest oscillator.
+3o!
+";
+
+    #[test]
+    fn parse_glider_test() {
+        let pattern = parse(GLIDER).unwrap();
+        assert_eq!(Some("Glider".to_string()), pattern.name);
+        assert_eq!(Some("Richard K. Guy".to_string()), pattern.author);
+        assert_eq!(2, pattern.comments.len());
+        assert_eq!(Some(Rule::CONWAY), pattern.rule);
+        assert_eq!(3, pattern.width());
+        assert_eq!(3, pattern.height());
+        assert_eq!(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], pattern.cells());
+    }
+    #[test]
+    fn write_glider_test() {
+        assert_eq!(GLIDER, write(&parse(GLIDER).unwrap()));
+    }
+    /// Tests runs spanning multiple lines, counts before `$`,
+    /// and text after `!` being ignored.
+    #[test]
+    fn parse_multiple_lines_test() {
+        let pattern = parse("x = 12, y = 4\n2o10b$\n12o2$\n1\n1o!\nignored").unwrap();
+        assert_eq!(None, pattern.rule);
+        let mut expected = vec![(0, 0), (1, 0)];
+        expected.extend((0..12).map(|x| (x, 1)));
+        expected.extend((0..11).map(|x| (x, 3)));
+        assert_eq!(&expected, pattern.cells());
+    }
+    /// Tests the grid Golly adds after the rule is left out of the rule,
+    /// even though its size has a comma like the fields of the header.
+    #[test]
+    fn parse_golly_grid_test() {
+        let pattern = parse(TORUS_GLIDER).unwrap();
+        assert_eq!(Some(Rule::CONWAY), pattern.rule);
+        assert_eq!((3, 3), (pattern.width(), pattern.height()));
+        assert_eq!(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)], pattern.cells());
+        let pattern = parse("x = 1, y = 1, rule = B36/S23:P8,4\no!").unwrap();
+        assert_eq!(Some(Rule::HIGHLIFE), pattern.rule);
+    }
+    /// Tests comments after the header are read as comments, not as cells.
+    #[test]
+    fn parse_comment_after_header_test() {
+        let pattern = parse(LATE_COMMENTS).unwrap();
+        assert_eq!(Some("Blinker".to_string()), pattern.name);
+        assert_eq!(vec!["The smallest oscillator.".to_string()], pattern.comments);
+        assert_eq!(&[(0, 0), (1, 0), (2, 0)], pattern.cells());
+    }
+    #[test]
+    fn parse_old_rule_comment_test() {
+        let pattern = parse("#r 23/36\nx = 1, y = 1\no!").unwrap();
+        assert_eq!(Some(Rule::new(&[3, 6], &[2, 3]).unwrap()), pattern.rule);
+    }
+    #[test]
+    fn parse_error_test() {
+        let error = |text: &str| {
+            let e = parse(text).unwrap_err();
+            (e.line(), e.column(), e.kind())
+        };
+        assert_eq!((2, 1, ParseRleErrorKind::MissingHeader), error("#N Nothing\n"));
+        assert_eq!((1, 1, ParseRleErrorKind::InvalidHeader), error("x = 3\nooo!"));
+        assert_eq!((1, 7, ParseRleErrorKind::InvalidHeader), error("x = 3, y = three\nooo!"));
+        assert_eq!((1, 14, ParseRleErrorKind::InvalidRule(ParseRuleErrorKind::InvalidCharacter('9'))), error("x = 3, y = 1, rule = B9/S23\nooo!"));
+        assert_eq!((3, 2, ParseRleErrorKind::InvalidCharacter('x')), error("x = 3, y = 2\nooo$\nox!"));
+        assert_eq!((2, 4, ParseRleErrorKind::OutOfBounds), error("x = 3, y = 1\nbo2o!"));
+        assert_eq!((2, 4, ParseRleErrorKind::OutOfBounds), error("x = 3, y = 2\no2$o!"));
+        assert_eq!((2, 2, ParseRleErrorKind::InvalidCount), error("x = 3, y = 1\n2 o!"));
+        assert_eq!((2, 3, ParseRleErrorKind::InvalidCount), error("x = 3, y = 1\no3!"));
+        assert_eq!((2, 20, ParseRleErrorKind::InvalidCount), error("x = 3, y = 1\n99999999999999999999999o!"));
+        assert_eq!((3, 1, ParseRleErrorKind::MissingTerminator), error("x = 3, y = 1\nooo\n"));
+    }
+    #[test]
+    fn display_error_test() {
+        let e = ParseRleError::new(3, 2, ParseRleErrorKind::InvalidCharacter('x'));
+        assert_eq!("line 3, column 2: unexpected character 'x'", e.to_string());
+    }
+    /// Tests lines are wrapped to keep them short.
+    #[test]
+    fn write_long_line_test() {
+        let pattern = Pattern::new(200, 1, (0..200).step_by(2).map(|x| (x, 0))).unwrap();
+        let text = write(&pattern);
+        assert!(text.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(pattern.cells(), parse(&text).unwrap().cells());
+    }
+    #[test]
+    fn grid_test() {
+        let pattern = parse(GLIDER).unwrap();
+        let mut grid = TestGrid::default();
+        pattern.write_to(&mut grid, 4, 5).unwrap();
+        assert_eq!(Ok(Cell::Alive), grid.get(5, 5));
+        assert_eq!(Ok(Cell::Dead), grid.get(4, 5));
+        let written = write(&Pattern::from_grid(&grid));
+        assert_eq!(format!("x = {WIDTH}, y = {HEIGHT}, rule = B3/S23\n5$5bo$6bo$4b3o!\n"), written);
+    }
+    proptest! {
+        /// Tests writing then parsing a pattern gives back the same pattern.
+        #[test]
+        fn round_trip_test(
+            width in 1usize..100,
+            height in 1usize..30,
+            cells in proptest::collection::vec((0usize..100, 0usize..30), 0..300),
+            name in proptest::option::of("[a-zA-Z0-9]([a-zA-Z0-9 ]*[a-zA-Z0-9])?"),
+        ) {
+            let cells = cells.into_iter().filter(|&(x, y)| x < width && y < height);
+            let mut pattern = Pattern::new(width, height, cells).unwrap();
+            pattern.name = name;
+            pattern.rule = Some(Rule::HIGHLIFE);
+            assert_eq!(pattern, parse(&write(&pattern)).unwrap());
+        }
+    }
+}
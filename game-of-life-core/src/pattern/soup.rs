@@ -0,0 +1,266 @@
+//! Random soups, rectangles filled with alive cells at random,
+//! optionally symmetric like the soups searched by apgsearch.
+//!
+//! A soup is decided entirely by its size, density, symmetry and seed,
+//! so sharing those is enough to share the soup.
+//! Seeds can be given as text with [`seed_of`](crate::random::seed_of).
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    grid::{CellOutOfBoundsError, Grid},
+    random::Rng,
+};
+
+use super::Pattern;
+
+/// Which symmetries a soup has, named as in apgsearch.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Symmetry {
+    /// No symmetry.
+    #[default]
+    C1,
+    /// The same when turned halfway around.
+    C2,
+    /// The same when turned a quarter of the way around, so the soup must be square.
+    C4,
+    /// The same when mirrored left to right.
+    D2,
+    /// The same when mirrored left to right, or top to bottom.
+    D4,
+    /// The same when turned or mirrored in any way, so the soup must be square.
+    D8,
+}
+
+impl Symmetry {
+    const ALL: [Symmetry; 6] = [Symmetry::C1, Symmetry::C2, Symmetry::C4, Symmetry::D2, Symmetry::D4, Symmetry::D8];
+
+    /// Returns true if the symmetry turns the soup a quarter of the way around, or mirrors it diagonally,
+    /// which only keeps a square soup the same size.
+    pub fn needs_square(&self) -> bool {
+        matches!(self, Symmetry::C4 | Symmetry::D8)
+    }
+    /// Returns the cells the symmetry maps `(x, y)` to in a soup of the given size,
+    /// including `(x, y)` itself.
+    fn images(&self, x: usize, y: usize, width: usize, height: usize) -> Vec<(usize, usize)> {
+        let (right, bottom) = (width - 1 - x, height - 1 - y);
+        match self {
+            Symmetry::C1 => vec![(x, y)],
+            Symmetry::C2 => vec![(x, y), (right, bottom)],
+            // Turning a square a quarter of the way around moves `(x, y)` to `(width - 1 - y, x)`.
+            Symmetry::C4 => vec![(x, y), (width - 1 - y, x), (right, bottom), (y, height - 1 - x)],
+            Symmetry::D2 => vec![(x, y), (right, y)],
+            Symmetry::D4 => vec![(x, y), (right, y), (x, bottom), (right, bottom)],
+            Symmetry::D8 => {
+                let mut images = Symmetry::D4.images(x, y, width, height);
+                images.extend(Symmetry::D4.images(y, x, width, height));
+                images
+            },
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = ParseSymmetryError;
+
+    /// Parses the name of a symmetry, ex. `D4`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Symmetry::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| ParseSymmetryError::new(s.trim().to_string()))
+    }
+}
+
+/// How to fill a rectangle of cells at random.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Soup {
+    width: usize,
+    height: usize,
+    density: f64,
+    symmetry: Symmetry,
+}
+
+impl Soup {
+    /// Creates a soup of the given size, where each cell is alive half of the time, with no symmetry.
+    pub fn new(width: usize, height: usize) -> Self {
+        Soup { width, height, density: 0.5, symmetry: Symmetry::default() }
+    }
+    /// Returns the soup with each cell alive with a probability of `density`, from 0 to 1.
+    pub fn with_density(self, density: f64) -> Self {
+        Soup { density, ..self }
+    }
+    /// Returns the soup with the `symmetry`,
+    /// or Err if the symmetry [needs a square](Symmetry::needs_square) and the soup is not.
+    pub fn with_symmetry(self, symmetry: Symmetry) -> Result<Self, NotSquareError> {
+        if symmetry.needs_square() && self.width != self.height {
+            return Err(NotSquareError::new(symmetry, self.width, self.height));
+        }
+        Ok(Soup { symmetry, ..self })
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+    /// Fills the soup from `seed`, always giving the same pattern for the same seed.
+    /// Only one cell of each set the symmetry maps onto each other is random,
+    /// the first of them row by row, and the rest copy it.
+    pub fn generate(&self, seed: u64) -> Pattern {
+        let mut rng = Rng::new(seed);
+        let mut cells = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let images = self.symmetry.images(x, y, self.width, self.height);
+                let first = images.iter().all(|&(ix, iy)| (y, x) <= (iy, ix));
+                if first && rng.chance(self.density) {
+                    cells.extend(images);
+                }
+            }
+        }
+        // Every image of a cell in the soup is in the soup.
+        Pattern::new(self.width, self.height, cells).unwrap()
+    }
+    /// Fills the cells of the grid covered by the soup from `seed`, with its top left corner at `(x, y)`,
+    /// then updates the grid.
+    /// Returns Err without changing the grid if the soup does not fit.
+    pub fn write_to(&self, grid: &mut impl Grid, x: usize, y: usize, seed: u64) -> Result<(), CellOutOfBoundsError> {
+        self.generate(seed).write_to(grid, x, y)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct NotSquareError {
+    symmetry: Symmetry,
+    width: usize,
+    height: usize,
+}
+
+impl NotSquareError {
+    pub(crate) fn new(symmetry: Symmetry, width: usize, height: usize) -> Self {
+        NotSquareError { symmetry, width, height }
+    }
+    pub fn symmetry(&self) -> Symmetry { self.symmetry }
+    pub fn width(&self) -> usize { self.width }
+    pub fn height(&self) -> usize { self.height }
+}
+impl std::error::Error for NotSquareError {}
+impl fmt::Display for NotSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} symmetry needs a square soup, not {}x{}", self.symmetry, self.width, self.height)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParseSymmetryError {
+    name: String,
+}
+
+impl ParseSymmetryError {
+    pub(crate) fn new(name: String) -> Self {
+        ParseSymmetryError { name }
+    }
+    pub fn name(&self) -> &str { &self.name }
+}
+impl std::error::Error for ParseSymmetryError {}
+impl fmt::Display for ParseSymmetryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a symmetry, expected C1, C2, C4, D2, D4 or D8", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::proptest;
+    use crate::{grid::Cell, random::seed_of, tests::*};
+
+    #[test]
+    fn parse_symmetry_test() {
+        for symmetry in Symmetry::ALL {
+            assert_eq!(Ok(symmetry), symmetry.to_string().parse());
+        }
+        assert_eq!(Ok(Symmetry::D8), " d8 ".parse());
+        assert_eq!("C3", "C3".parse::<Symmetry>().unwrap_err().name());
+        assert_eq!(
+            "'C3' is not a symmetry, expected C1, C2, C4, D2, D4 or D8",
+            "C3".parse::<Symmetry>().unwrap_err().to_string(),
+        );
+    }
+    #[test]
+    fn not_square_test() {
+        let e = Soup::new(4, 3).with_symmetry(Symmetry::C4).unwrap_err();
+        assert_eq!(NotSquareError::new(Symmetry::C4, 4, 3), e);
+        assert_eq!("C4 symmetry needs a square soup, not 4x3", e.to_string());
+        assert!(Soup::new(4, 3).with_symmetry(Symmetry::D8).is_err());
+        assert!(Soup::new(4, 3).with_symmetry(Symmetry::D4).is_ok());
+        assert!(Soup::new(4, 4).with_symmetry(Symmetry::D8).is_ok());
+    }
+    #[test]
+    fn density_test() {
+        assert!(Soup::new(16, 16).with_density(0.0).generate(1).cells().is_empty());
+        assert_eq!(256, Soup::new(16, 16).with_density(1.0).generate(1).cells().len());
+        let alive = Soup::new(100, 100).with_density(0.3).generate(seed_of("density")).cells().len();
+        assert!((2600..3400).contains(&alive), "{alive}");
+    }
+    #[test]
+    fn write_to_test() {
+        let soup = Soup::new(4, 4).with_density(1.0);
+        let mut grid = TestGrid::default();
+        soup.write_to(&mut grid, 2, 3, 0).unwrap();
+        assert_eq!(16, grid.population());
+        assert_eq!(Ok(Cell::Alive), grid.get(5, 6));
+        assert_eq!(Ok(Cell::Dead), grid.get(6, 6));
+        assert_eq!(CellOutOfBoundsError::new(WIDTH, 4), soup.write_to(&mut grid, WIDTH - 3, 1, 0).unwrap_err());
+    }
+    proptest! {
+        /// Tests a soup is the same every time it is generated from the same seed.
+        #[test]
+        fn same_seed_test(seed: u64, width in 0usize..20, height in 0usize..20, density in 0.0..=1.0) {
+            let soup = Soup::new(width, height).with_density(density);
+            assert_eq!(soup.generate(seed), soup.generate(seed));
+        }
+        /// Tests a soup with each symmetry is the same after every turn or mirror of the symmetry.
+        #[test]
+        fn symmetry_test(
+            seed: u64,
+            width in 1usize..16,
+            height in 1usize..16,
+            symmetry in proptest::sample::select(Symmetry::ALL.to_vec()),
+        ) {
+            let height = if symmetry.needs_square() { width } else { height };
+            let soup = Soup::new(width, height).with_symmetry(symmetry).unwrap();
+            let pattern = soup.generate(seed);
+            for &(x, y) in pattern.cells() {
+                for image in symmetry.images(x, y, width, height) {
+                    assert!(pattern.cells().contains(&image), "{image:?} of {:?}", (x, y));
+                }
+            }
+        }
+        /// Tests no symmetry draws a random number for every cell,
+        /// so it matches drawing the cells one by one from the same seed.
+        #[test]
+        fn c1_test(seed: u64, width in 0usize..12, height in 0usize..12) {
+            let pattern = Soup::new(width, height).generate(seed);
+            let mut rng = Rng::new(seed);
+            let expected: Vec<(usize, usize)> = (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter(|_| rng.chance(0.5))
+                .collect();
+            assert_eq!(expected, pattern.cells());
+        }
+    }
+}
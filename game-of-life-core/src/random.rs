@@ -0,0 +1,88 @@
+//! A small seeded random number generator,
+//! so anything random in a game can be repeated exactly from its seed.
+
+/// A SplitMix64 random number generator.
+/// It is fast and passes common statistical tests, but is not suitable for cryptography.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator which gives the same numbers every time it is created with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+    /// Returns the next random number, any `u64` being equally likely.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Returns a random number from 0 up to but not including 1.
+    pub fn next_f64(&mut self) -> f64 {
+        // The top 53 bits fill the mantissa of an `f64` exactly.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    /// Returns true with the given probability.
+    /// Probabilities of 1 or more are always true, and 0 or less (or NaN) never are.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+}
+
+/// Turns text into a seed, so a seed can be shared as a word or phrase.
+/// The same text always gives the same seed, using the 64-bit FNV-1a hash.
+pub fn seed_of(text: &str) -> u64 {
+    text.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::proptest;
+
+    /// Tests the first numbers match the reference SplitMix64 from a seed of 0.
+    #[test]
+    fn reference_test() {
+        let mut rng = Rng::new(0);
+        assert_eq!(0xE220_A839_7B1D_CDAF, rng.next_u64());
+        assert_eq!(0x6E78_9E6A_A1B9_65F4, rng.next_u64());
+    }
+    #[test]
+    fn seed_of_test() {
+        assert_eq!(0xCBF2_9CE4_8422_2325, seed_of(""));
+        assert_eq!(0xAF63_DC4C_8601_EC8C, seed_of("a"));
+        assert_ne!(seed_of("soup 1"), seed_of("soup 2"));
+    }
+    #[test]
+    fn chance_frequency_test() {
+        let mut rng = Rng::new(7);
+        let hits = (0..10_000).filter(|_| rng.chance(0.25)).count();
+        assert!((2300..2700).contains(&hits), "{hits}");
+    }
+    proptest! {
+        /// Tests two generators with the same seed give the same numbers.
+        #[test]
+        fn same_seed_test(seed: u64) {
+            let (mut a, mut b) = (Rng::new(seed), Rng::new(seed));
+            for _ in 0..20 {
+                assert_eq!(a.next_u64(), b.next_u64());
+            }
+        }
+        /// Tests random fractions are from 0 up to 1, and certain chances always or never happen.
+        #[test]
+        fn next_f64_test(seed: u64) {
+            let mut rng = Rng::new(seed);
+            for _ in 0..20 {
+                let fraction = rng.next_f64();
+                assert!((0.0..1.0).contains(&fraction));
+                assert!(rng.chance(1.0));
+                assert!(!rng.chance(0.0));
+                assert!(!rng.chance(f64::NAN));
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! Configurations of alive neighbors, for isotropic non-totalistic rules in Hensel notation.
+//!
+//! Such rules decide whether a cell is born or survives from how its alive neighbors are arranged,
+//! not only how many there are, ex. `B2-a/S12`.
+//! Arrangements which are the same after rotating or reflecting them are the same configuration,
+//! leaving 51 configurations, each named by its number of alive neighbors and a letter, ex. `2a`.
+//! No alive neighbors and eight alive neighbors have a single configuration each, named `0` and `8`.
+
+use std::fmt;
+
+use crate::grid::NEIGHBOR_OFFSETS;
+
+/// The number of configurations of alive neighbors.
+pub const CONFIGURATIONS: usize = 51;
+
+/// The letters naming the configurations of each number of alive neighbors, in the order they are written.
+pub(crate) const LETTERS: [&str; 9] = ["", "ce", "cekain", "cekainyqjr", "cekainyqjrtwz", "cekainyqjr", "cekain", "ce", ""];
+
+/// The index of the first configuration of each number of alive neighbors,
+/// followed by the number of configurations.
+const FIRST: [u8; 10] = [0, 1, 3, 9, 19, 32, 42, 48, 50, 51];
+
+/// One arrangement of each configuration of up to four alive neighbors, in the order of [`LETTERS`],
+/// as a 3x3 block where bit `3 * (dy + 1) + (dx + 1)` is the neighbor at `(dx, dy)`.
+/// The configurations of more than four neighbors are those of fewer neighbors
+/// with every neighbor flipped, ex. `5a` is `3a` flipped.
+const BLOCKS: [&[u16]; 5] = [
+    &[0],
+    &[1, 2],
+    &[5, 10, 33, 3, 40, 68],
+    &[69, 42, 98, 11, 7, 13, 97, 70, 14, 41],
+    &[325, 170, 99, 15, 45, 71, 101, 102, 106, 43, 105, 78, 108],
+];
+
+/// The configuration of every neighborhood.
+const TABLE: [u8; 256] = table();
+
+/// The way the alive neighbors of a cell are arranged,
+/// up to rotation and reflection.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct Configuration {
+    // From 0 to 50, ordered by the number of alive neighbors,
+    // then by the order of the letters in `LETTERS`.
+    index: u8,
+}
+
+impl Configuration {
+    /// Returns the configuration of a neighborhood,
+    /// where each bit is set if its neighbor is alive.
+    /// Bit 0 is the neighbor below the cell (`y + 1`),
+    /// and the rest follow counter-clockwise:
+    /// below-right, right, above-right, above, above-left, left, below-left.
+    pub fn of(neighborhood: u8) -> Self {
+        Configuration { index: TABLE[neighborhood as usize] }
+    }
+    /// Returns the configuration of `count` alive neighbors named by `letter`,
+    /// or None if there is no such configuration.
+    /// The configurations of 0 and 8 alive neighbors are the ones with no letter.
+    pub fn new(count: usize, letter: Option<char>) -> Option<Self> {
+        let letters = LETTERS.get(count)?;
+        let offset = match letter {
+            None if letters.is_empty() => 0,
+            None => return None,
+            Some(letter) => letters.find(letter)?,
+        };
+        Some(Configuration { index: FIRST[count] + offset as u8 })
+    }
+    /// Returns every configuration, ordered by the number of alive neighbors.
+    pub fn all() -> impl Iterator<Item = Configuration> {
+        (0..CONFIGURATIONS as u8).map(|index| Configuration { index })
+    }
+    /// Returns every configuration of `count` alive neighbors,
+    /// as a mask where bit `n` is set for the configuration with index `n`.
+    pub(crate) const fn mask_of_count(count: usize) -> u64 {
+        if count >= FIRST.len() - 1 {
+            return 0;
+        }
+        (1 << FIRST[count + 1]) - (1 << FIRST[count])
+    }
+    pub(crate) fn mask(&self) -> u64 {
+        1 << self.index
+    }
+    /// Returns the number of alive neighbors.
+    pub fn count(&self) -> usize {
+        // The index is always below the last entry of `FIRST`.
+        FIRST.iter().rposition(|&first| first <= self.index).unwrap()
+    }
+    /// Returns the letter naming the configuration,
+    /// or None for the configurations of 0 and 8 alive neighbors.
+    pub fn letter(&self) -> Option<char> {
+        let count = self.count();
+        LETTERS[count].chars().nth((self.index - FIRST[count]) as usize)
+    }
+}
+
+impl fmt::Display for Configuration {
+    /// Writes the number of alive neighbors followed by the letter, ex. `2a`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.count())?;
+        if let Some(letter) = self.letter() {
+            write!(f, "{letter}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds [`TABLE`] by giving every rotation and reflection
+/// of each arrangement in [`BLOCKS`] its configuration.
+const fn table() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut count = 0;
+    while count <= 8 {
+        let mut index = FIRST[count];
+        while index < FIRST[count + 1] {
+            let offset = (index - FIRST[count]) as usize;
+            let neighborhood = if count <= 4 {
+                neighborhood_of(BLOCKS[count][offset])
+            } else {
+                !neighborhood_of(BLOCKS[8 - count][offset])
+            };
+            let mut rotation = 0;
+            while rotation < 4 {
+                // The neighbors go around the cell in order,
+                // so turning the neighborhood a quarter turn is moving each neighbor two places,
+                // and reversing their order and moving them one place mirrors it.
+                let rotated = neighborhood.rotate_left(2 * rotation);
+                table[rotated as usize] = index;
+                table[rotated.reverse_bits().rotate_left(1) as usize] = index;
+                rotation += 1;
+            }
+            index += 1;
+        }
+        count += 1;
+    }
+    table
+}
+
+/// Converts a 3x3 block from [`BLOCKS`] to a neighborhood for [`Configuration::of`].
+const fn neighborhood_of(block: u16) -> u8 {
+    let mut neighborhood = 0;
+    let mut i = 0;
+    while i < NEIGHBOR_OFFSETS.len() {
+        let (dx, dy) = NEIGHBOR_OFFSETS[i];
+        if block >> (3 * (dy + 1) + dx + 1) & 1 != 0 {
+            neighborhood |= 1 << i;
+        }
+        i += 1;
+    }
+    neighborhood
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    /// Tests there are 51 configurations, each named once,
+    /// and every neighborhood of each configuration has the same number of alive neighbors.
+    #[test]
+    fn configurations_test() {
+        let names: HashSet<String> = Configuration::all().map(|configuration| configuration.to_string()).collect();
+        assert_eq!(CONFIGURATIONS, names.len());
+        for (count, letters) in LETTERS.iter().enumerate() {
+            let expected = letters.len().max(1);
+            assert_eq!(expected, Configuration::all().filter(|configuration| configuration.count() == count).count());
+        }
+        let mut seen = HashSet::new();
+        for neighborhood in 0..=u8::MAX {
+            let configuration = Configuration::of(neighborhood);
+            assert_eq!(neighborhood.count_ones() as usize, configuration.count());
+            seen.insert(configuration);
+        }
+        assert_eq!(CONFIGURATIONS, seen.len());
+    }
+    /// Tests the number of neighborhoods of each configuration,
+    /// which is how many different ways it can be rotated and reflected.
+    #[test]
+    fn configuration_sizes_test() {
+        let size = |name: &str| {
+            (0..=u8::MAX)
+                .filter(|&neighborhood| Configuration::of(neighborhood).to_string() == name)
+                .count()
+        };
+        assert_eq!(1, size("0"));
+        assert_eq!(4, size("1c"));
+        assert_eq!(4, size("1e"));
+        assert_eq!(8, size("2k"));
+        assert_eq!(2, size("2i"));
+        assert_eq!(2, size("2n"));
+        assert_eq!(1, size("4c"));
+        assert_eq!(1, size("4e"));
+        assert_eq!(8, size("4r"));
+        assert_eq!(4, size("4z"));
+        assert_eq!(4, size("5k"));
+        assert_eq!(1, size("8"));
+    }
+    /// Tests the arrangements each letter is named for.
+    #[test]
+    fn letters_test() {
+        // Neighbors by direction, using the bits of `Configuration::of`.
+        const S: u8 = 1;
+        const SE: u8 = 1 << 1;
+        const E: u8 = 1 << 2;
+        const NE: u8 = 1 << 3;
+        const N: u8 = 1 << 4;
+        const NW: u8 = 1 << 5;
+        const W: u8 = 1 << 6;
+        const SW: u8 = 1 << 7;
+        let name = |neighborhood: u8| Configuration::of(neighborhood).to_string();
+        assert_eq!("1c", name(NE));
+        assert_eq!("1e", name(W));
+        assert_eq!("2a", name(N | NE));
+        assert_eq!("2c", name(NE | SE));
+        assert_eq!("2e", name(N | E));
+        assert_eq!("2i", name(N | S));
+        assert_eq!("2k", name(N | SE));
+        assert_eq!("2n", name(NW | SE));
+        assert_eq!("3i", name(NW | N | NE));
+        assert_eq!("3y", name(NW | NE | S));
+        assert_eq!("4e", name(N | E | S | W));
+        assert_eq!("4t", name(NW | N | NE | S));
+        assert_eq!("4w", name(NW | W | S | SE));
+        assert_eq!("4z", name(NW | N | S | SE));
+        assert_eq!("5e", name(!(N | E | W)));
+        assert_eq!("7c", name(!SW));
+        assert_eq!("8", name(u8::MAX));
+        // One arrangement of every configuration.
+        // Five or more neighbors are the arrangements of fewer neighbors flipped.
+        let table = [
+            ("0", 0),
+            ("1c", NW),
+            ("1e", N),
+            ("2c", NW | NE),
+            ("2e", N | W),
+            ("2k", NW | E),
+            ("2a", NW | N),
+            ("2i", W | E),
+            ("2n", NE | SW),
+            ("3c", NW | NE | SW),
+            ("3e", N | W | E),
+            ("3k", N | E | SW),
+            ("3a", NW | N | W),
+            ("3i", NW | N | NE),
+            ("3n", NW | NE | W),
+            ("3y", NW | E | SW),
+            ("3q", N | NE | SW),
+            ("3j", N | NE | W),
+            ("3r", NW | W | E),
+            ("4c", NW | NE | SW | SE),
+            ("4e", N | W | E | S),
+            ("4k", NW | N | E | SW),
+            ("4a", NW | N | NE | W),
+            ("4i", NW | NE | W | E),
+            ("4n", NW | N | NE | SW),
+            ("4y", NW | NE | E | SW),
+            ("4q", N | NE | E | SW),
+            ("4j", N | W | E | SW),
+            ("4r", NW | N | W | E),
+            ("4t", NW | W | E | SW),
+            ("4w", N | NE | W | SW),
+            ("4z", NE | W | E | SW),
+            ("5c", !(NW | NE | SW)),
+            ("5e", !(N | W | E)),
+            ("5k", !(N | E | SW)),
+            ("5a", !(NW | N | W)),
+            ("5i", !(NW | N | NE)),
+            ("5n", !(NW | NE | W)),
+            ("5y", !(NW | E | SW)),
+            ("5q", !(N | NE | SW)),
+            ("5j", !(N | NE | W)),
+            ("5r", !(NW | W | E)),
+            ("6c", !(NW | NE)),
+            ("6e", !(N | W)),
+            ("6k", !(NW | E)),
+            ("6a", !(NW | N)),
+            ("6i", !(W | E)),
+            ("6n", !(NE | SW)),
+            ("7c", !NW),
+            ("7e", !N),
+            ("8", u8::MAX),
+        ];
+        assert_eq!(CONFIGURATIONS, table.len());
+        for (expected, neighborhood) in table {
+            assert_eq!(expected, name(neighborhood));
+        }
+        let names: Vec<String> = Configuration::all().map(|configuration| configuration.to_string()).collect();
+        assert_eq!(names, table.map(|(name, _)| name));
+    }
+    #[test]
+    fn new_test() {
+        assert_eq!(Some("0".to_string()), Configuration::new(0, None).map(|c| c.to_string()));
+        assert_eq!(Some("3q".to_string()), Configuration::new(3, Some('q')).map(|c| c.to_string()));
+        assert_eq!(Some("8".to_string()), Configuration::new(8, None).map(|c| c.to_string()));
+        assert_eq!(None, Configuration::new(2, Some('y')));
+        assert_eq!(None, Configuration::new(1, Some('a')));
+        assert_eq!(None, Configuration::new(8, Some('c')));
+        assert_eq!(None, Configuration::new(2, None));
+        assert_eq!(None, Configuration::new(9, None));
+    }
+    proptest! {
+        /// Tests rotating or mirroring a neighborhood keeps its configuration.
+        #[test]
+        fn symmetry_test(neighborhood: u8, quarter_turns in 0u32..4) {
+            let configuration = Configuration::of(neighborhood);
+            let rotated = neighborhood.rotate_left(2 * quarter_turns);
+            assert_eq!(configuration, Configuration::of(rotated));
+            assert_eq!(configuration, Configuration::of(rotated.reverse_bits().rotate_left(1)));
+        }
+        /// Tests flipping every neighbor keeps the letter,
+        /// changing the number of alive neighbors from `n` to `8 - n`.
+        /// Flipping four neighbors leaves four, which can be a different configuration.
+        #[test]
+        fn flipped_test(neighborhood: u8) {
+            let configuration = Configuration::of(neighborhood);
+            let flipped = Configuration::of(!neighborhood);
+            assert_eq!(8 - configuration.count(), flipped.count());
+            if configuration.count() != 4 {
+                assert_eq!(configuration.letter(), flipped.letter());
+            }
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Larger than Life rules, which count the alive cells within a range of each cell,
+//! written like `R5,C0,M1,S34..58,B34..45,NM` (Bosco's Rule).
+//!
+//! The sections of the rulestring are
+//! - `R`, the range of the neighborhood, from 1 to [`MAX_RANGE`].
+//! - `C`, the number of states, where `C0` and `C2` have no dying states.
+//! - `M`, `1` if the cell counts itself as a neighbor, `0` if not.
+//! - `S`, the lowest and highest count at which an alive cell survives.
+//! - `B`, the lowest and highest count at which a dead cell is born.
+//! - `N`, optionally, `M` for the square neighborhood, or `N` for the diamond (von Neumann) neighborhood.
+
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use crate::grid::{neighborhood::Neighborhood, topology::Topology, Cell, Grid};
+
+use super::Transition;
+
+/// The largest range a rule can have.
+pub const MAX_RANGE: usize = 500;
+
+/// A Larger than Life rule, where cells are born or survive
+/// when the number of alive cells around them is within a range of counts.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LargerThanLife {
+    range: usize,
+    states: u8,
+    middle: bool,
+    // The lowest and highest counts, inclusive.
+    survival: (usize, usize),
+    birth: (usize, usize),
+    von_neumann: bool,
+}
+
+impl LargerThanLife {
+    /// Returns how far away the neighbors of a cell can be.
+    pub fn range(&self) -> usize {
+        self.range
+    }
+    /// Returns the number of states a cell can be in, counting alive and dead.
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    /// Returns true if an alive cell counts itself as one of its neighbors.
+    pub fn counts_middle(&self) -> bool {
+        self.middle
+    }
+    /// Returns the counts at which an alive cell survives.
+    pub fn survival(&self) -> RangeInclusive<usize> {
+        self.survival.0..=self.survival.1
+    }
+    /// Returns the counts at which a dead cell is born.
+    pub fn birth(&self) -> RangeInclusive<usize> {
+        self.birth.0..=self.birth.1
+    }
+    /// Returns the neighborhood the rule counts in, without the cell itself.
+    pub fn neighborhood(&self) -> Neighborhood {
+        if self.von_neumann {
+            Neighborhood::ExtendedVonNeumann(self.range)
+        } else {
+            Neighborhood::ExtendedMoore(self.range)
+        }
+    }
+    /// Returns the highest count a cell can have.
+    fn max_count(&self) -> usize {
+        let side = 2 * self.range + 1;
+        let cells = if self.von_neumann { 2 * self.range * (self.range + 1) + 1 } else { side * side };
+        if self.middle { cells } else { cells - 1 }
+    }
+    /// Returns the state `cell` changes to during the next generation,
+    /// given `count` alive cells in its neighborhood,
+    /// which includes the cell itself if the rule [counts the middle](LargerThanLife::counts_middle).
+    pub fn next_cell(&self, cell: Cell, count: usize) -> Cell {
+        match cell {
+            Cell::Dead if self.birth().contains(&count) => Cell::Alive,
+            Cell::Dead => Cell::Dead,
+            Cell::Alive if self.survival().contains(&count) => Cell::Alive,
+            Cell::Alive => self.decay(0),
+            Cell::Dying(age) => self.decay(age),
+        }
+    }
+    /// Returns the state after a cell has been dying for `age` generations,
+    /// `0` being a cell which was just alive.
+    fn decay(&self, age: u8) -> Cell {
+        match age.checked_add(1) {
+            Some(age) if age <= self.states - 2 => Cell::Dying(age),
+            _ => Cell::Dead,
+        }
+    }
+    /// Changes every cell of the grid to its state in the next generation,
+    /// with the edges of the grid joined by `topology`.
+    ///
+    /// The alive cells are counted once into a table of sums,
+    /// so counting the square neighborhood of a cell takes the same time at any range,
+    /// and the diamond neighborhood takes a sum for each of its rows.
+    pub fn evolve(&self, grid: &mut impl Grid, topology: Topology) {
+        let (width, height) = (grid.width(), grid.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+        let range = self.range;
+        let (padded_width, padded_height) = (width + 2 * range, height + 2 * range);
+        let stride = padded_width + 1;
+        // The grid with `range` cells around it, holding the cells the topology puts past each edge.
+        // `rows[py * stride + px]` counts the alive cells to the left of column `px` in row `py`.
+        let mut rows = vec![0; padded_height * stride];
+        for py in 0..padded_height {
+            for px in 0..padded_width {
+                let (dx, dy) = (px as isize - range as isize, py as isize - range as isize);
+                let alive = topology
+                    .offset(0, 0, dx, dy, width, height)
+                    .is_some_and(|(x, y)| grid.get(x, y) == Ok(Cell::Alive));
+                rows[py * stride + px + 1] = rows[py * stride + px] + usize::from(alive);
+            }
+        }
+        // `table[py * stride + px]` counts the alive cells above row `py` and to the left of column `px`.
+        let mut table = Vec::new();
+        if !self.von_neumann {
+            table = vec![0; (padded_height + 1) * stride];
+            for py in 0..padded_height {
+                for px in 0..stride {
+                    table[(py + 1) * stride + px] = table[py * stride + px] + rows[py * stride + px];
+                }
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                // The cell is at `(x + range, y + range)` in the padded grid.
+                let count = if self.von_neumann {
+                    (0..=2 * range)
+                        .map(|py| {
+                            let reach = range - py.abs_diff(range);
+                            let row = (y + py) * stride + x + range;
+                            rows[row + reach + 1] - rows[row - reach]
+                        })
+                        .sum()
+                } else {
+                    let (top, bottom) = (y * stride, (y + 2 * range + 1) * stride);
+                    let (left, right) = (x, x + 2 * range + 1);
+                    table[bottom + right] + table[top + left] - table[top + right] - table[bottom + left]
+                };
+                // We know the cell is in bounds
+                // because we use the grid's width and height
+                // for coordinates.
+                let cell = grid.get(x, y).unwrap();
+                let count = if !self.middle && cell == Cell::Alive { count - 1 } else { count };
+                grid.set(x, y, self.next_cell(cell, count)).unwrap();
+            }
+        }
+        grid.update();
+    }
+}
+
+/// The neighbors are expected to be the rule's [neighborhood](LargerThanLife::neighborhood),
+/// the cell itself is counted when the rule counts the middle.
+impl Transition<Cell> for LargerThanLife {
+    fn next_state(&self, cell: Cell, neighbors: &[Cell]) -> Cell {
+        let alive = neighbors.iter().filter(|&&neighbor| neighbor == Cell::Alive).count();
+        let middle = usize::from(self.middle && cell == Cell::Alive);
+        self.next_cell(cell, alive + middle)
+    }
+}
+
+impl fmt::Display for LargerThanLife {
+    /// Writes the rule with every section, ex. `R5,C0,M1,S34..58,B34..45,NM`,
+    /// with `C0` when there are no dying states.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let states = if self.states == 2 { 0 } else { self.states };
+        write!(
+            f,
+            "R{},C{states},M{},S{}..{},B{}..{},N{}",
+            self.range,
+            u8::from(self.middle),
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1,
+            if self.von_neumann { 'N' } else { 'M' },
+        )
+    }
+}
+
+impl FromStr for LargerThanLife {
+    type Err = ParseLargerThanLifeError;
+
+    /// Parses a rulestring such as `R5,C0,M1,S34..58,B34..45,NM`.
+    /// The sections can be in any order, and every section but `N` is required.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |kind| Err(ParseLargerThanLifeError::new(kind));
+        let (mut range, mut states, mut middle, mut survival, mut birth, mut von_neumann) =
+            (None, None, None, None, None, None);
+        for section in s.trim().split(',') {
+            let mut chars = section.chars();
+            let Some(label) = chars.next() else {
+                return error(ParseLargerThanLifeErrorKind::EmptySection);
+            };
+            let label = label.to_ascii_uppercase();
+            let value = chars.as_str();
+            let invalid = || ParseLargerThanLifeError::new(ParseLargerThanLifeErrorKind::InvalidValue(label));
+            let number = || value.parse::<usize>().map_err(|_| invalid());
+            let (slot, parsed): (&mut Option<usize>, usize) = match label {
+                'R' => (&mut range, number()?),
+                'C' => (&mut states, number()?),
+                'M' => match value {
+                    "0" | "1" => (&mut middle, number()?),
+                    _ => return Err(invalid()),
+                },
+                'N' => match value {
+                    "M" | "m" => (&mut von_neumann, 0),
+                    "N" | "n" => (&mut von_neumann, 1),
+                    _ => return Err(invalid()),
+                },
+                'S' | 'B' => {
+                    let (min, max) = value.split_once("..").ok_or_else(invalid)?;
+                    let counts = (min.parse().map_err(|_| invalid())?, max.parse().map_err(|_| invalid())?);
+                    let counts_slot = if label == 'S' { &mut survival } else { &mut birth };
+                    if counts_slot.replace(counts).is_some() {
+                        return error(ParseLargerThanLifeErrorKind::DuplicateSection(label));
+                    }
+                    continue;
+                },
+                _ => return error(ParseLargerThanLifeErrorKind::UnknownSection(label)),
+            };
+            if slot.replace(parsed).is_some() {
+                return error(ParseLargerThanLifeErrorKind::DuplicateSection(label));
+            }
+        }
+        let missing = |label| ParseLargerThanLifeError::new(ParseLargerThanLifeErrorKind::MissingSection(label));
+        let range = range.ok_or_else(|| missing('R'))?;
+        if !(1..=MAX_RANGE).contains(&range) {
+            return error(ParseLargerThanLifeErrorKind::InvalidRange(range));
+        }
+        let states = match states.ok_or_else(|| missing('C'))? {
+            0 => 2,
+            states => match u8::try_from(states) {
+                Ok(states) if states >= 2 => states,
+                _ => return error(ParseLargerThanLifeErrorKind::InvalidStates(states)),
+            },
+        };
+        let rule = LargerThanLife {
+            range,
+            states,
+            middle: middle.ok_or_else(|| missing('M'))? == 1,
+            survival: survival.ok_or_else(|| missing('S'))?,
+            birth: birth.ok_or_else(|| missing('B'))?,
+            von_neumann: von_neumann == Some(1),
+        };
+        for (label, (min, max)) in [('S', rule.survival), ('B', rule.birth)] {
+            if min > max || max > rule.max_count() {
+                return error(ParseLargerThanLifeErrorKind::InvalidCounts(label));
+            }
+        }
+        Ok(rule)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParseLargerThanLifeError {
+    kind: ParseLargerThanLifeErrorKind,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ParseLargerThanLifeErrorKind {
+    /// Two commas with nothing between them.
+    EmptySection,
+    /// A section labeled with a letter other than `R`, `C`, `M`, `S`, `B` or `N`.
+    UnknownSection(char),
+    /// The rulestring has the same section twice.
+    DuplicateSection(char),
+    /// The rulestring is missing a section which is required.
+    MissingSection(char),
+    /// The value of a section could not be read.
+    InvalidValue(char),
+    /// A range of 0 or greater than [`MAX_RANGE`].
+    InvalidRange(usize),
+    /// A number of states of 1 or greater than 255.
+    InvalidStates(usize),
+    /// The lowest count of a section is above its highest,
+    /// or its highest is above the number of cells in the neighborhood.
+    InvalidCounts(char),
+}
+
+impl ParseLargerThanLifeError {
+    pub(crate) fn new(kind: ParseLargerThanLifeErrorKind) -> Self {
+        ParseLargerThanLifeError { kind }
+    }
+    pub fn kind(&self) -> ParseLargerThanLifeErrorKind { self.kind }
+}
+impl std::error::Error for ParseLargerThanLifeError {}
+impl fmt::Display for ParseLargerThanLifeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseLargerThanLifeErrorKind::EmptySection => write!(f, "rule has an empty section"),
+            ParseLargerThanLifeErrorKind::UnknownSection(label) => write!(f, "'{label}' is not a section of a rule"),
+            ParseLargerThanLifeErrorKind::DuplicateSection(label) => write!(f, "rule has the '{label}' section twice"),
+            ParseLargerThanLifeErrorKind::MissingSection(label) => write!(f, "rule is missing the '{label}' section"),
+            ParseLargerThanLifeErrorKind::InvalidValue(label) => write!(f, "the '{label}' section has an invalid value"),
+            ParseLargerThanLifeErrorKind::InvalidRange(range) => {
+                write!(f, "{range} is not a range from 1 to {MAX_RANGE}")
+            },
+            ParseLargerThanLifeErrorKind::InvalidStates(states) => {
+                write!(f, "{states} is not a number of states from 2 to 255")
+            },
+            ParseLargerThanLifeErrorKind::InvalidCounts(label) => {
+                write!(f, "the counts of the '{label}' section are out of order or larger than the neighborhood")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{evolve_each_cell, evolve_with, grid::dynamic::DynGrid, rule::Rule, Universe};
+
+    const BOSCO: &str = "R5,C0,M1,S34..58,B34..45,NM";
+    const ALL: [Topology; 5] = [
+        Topology::Bounded,
+        Topology::Torus,
+        Topology::Cylinder,
+        Topology::KleinBottle,
+        Topology::CrossSurface,
+    ];
+
+    #[test]
+    fn parse_test() {
+        let bosco: LargerThanLife = BOSCO.parse().unwrap();
+        assert_eq!(5, bosco.range());
+        assert_eq!(2, bosco.states());
+        assert!(bosco.counts_middle());
+        assert_eq!(34..=58, bosco.survival());
+        assert_eq!(34..=45, bosco.birth());
+        assert_eq!(Neighborhood::ExtendedMoore(5), bosco.neighborhood());
+        let rule: LargerThanLife = "r2,c3,m0,s1..4,b3..3,nn".parse().unwrap();
+        assert_eq!(3, rule.states());
+        assert!(!rule.counts_middle());
+        assert_eq!(Neighborhood::ExtendedVonNeumann(2), rule.neighborhood());
+        // The neighborhood is optional, and the sections can be in any order.
+        assert_eq!(Ok(bosco), "B34..45,S34..58,M1,C2,R5".parse());
+    }
+    #[test]
+    fn parse_error_test() {
+        let kind = |s: &str| s.parse::<LargerThanLife>().unwrap_err().kind();
+        assert_eq!(ParseLargerThanLifeErrorKind::EmptySection, kind("R5,,C0,M1,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::UnknownSection('X'), kind("R5,C0,M1,S34..58,B34..45,X1"));
+        assert_eq!(ParseLargerThanLifeErrorKind::DuplicateSection('R'), kind("R5,R4,C0,M1,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::DuplicateSection('B'), kind("R5,C0,M1,S34..58,B34..45,B1..2"));
+        assert_eq!(ParseLargerThanLifeErrorKind::MissingSection('M'), kind("R5,C0,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::MissingSection('B'), kind("R5,C0,M1,S34..58"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidValue('M'), kind("R5,C0,M2,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidValue('N'), kind("R5,C0,M1,S34..58,B34..45,NH"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidValue('S'), kind("R5,C0,M1,S34,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidValue('R'), kind("Rx,C0,M1,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidRange(0), kind("R0,C0,M1,S0..0,B0..0"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidRange(501), kind("R501,C0,M1,S0..0,B0..0"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidStates(1), kind("R5,C1,M1,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidStates(256), kind("R5,C256,M1,S34..58,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidCounts('S'), kind("R5,C0,M1,S58..34,B34..45"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidCounts('B'), kind("R1,C0,M1,S2..3,B3..10"));
+        assert_eq!(ParseLargerThanLifeErrorKind::InvalidCounts('B'), kind("R1,C0,M1,S2..3,B3..6,NN"));
+    }
+    #[test]
+    fn display_test() {
+        assert_eq!(BOSCO, BOSCO.parse::<LargerThanLife>().unwrap().to_string());
+        assert_eq!("R2,C3,M0,S1..4,B3..3,NN", "r2,c3,m0,s1..4,b3..3,nn".parse::<LargerThanLife>().unwrap().to_string());
+        assert_eq!(BOSCO, "R5,C2,M1,S34..58,B34..45".parse::<LargerThanLife>().unwrap().to_string());
+    }
+    #[test]
+    fn display_error_test() {
+        let e = ParseLargerThanLifeError::new(ParseLargerThanLifeErrorKind::InvalidRange(0));
+        assert_eq!("0 is not a range from 1 to 500", e.to_string());
+        let e = ParseLargerThanLifeError::new(ParseLargerThanLifeErrorKind::MissingSection('B'));
+        assert_eq!("rule is missing the 'B' section", e.to_string());
+    }
+    /// Tests a cell which does not survive passes through
+    /// every dying state before it is dead, and can not be born while dying.
+    #[test]
+    fn next_cell_test() {
+        let rule: LargerThanLife = "R2,C4,M0,S3..5,B4..6".parse().unwrap();
+        assert_eq!(Cell::Alive, rule.next_cell(Cell::Dead, 4));
+        assert_eq!(Cell::Dead, rule.next_cell(Cell::Dead, 3));
+        assert_eq!(Cell::Alive, rule.next_cell(Cell::Alive, 3));
+        assert_eq!(Cell::Dying(1), rule.next_cell(Cell::Alive, 6));
+        assert_eq!(Cell::Dying(2), rule.next_cell(Cell::Dying(1), 4));
+        assert_eq!(Cell::Dead, rule.next_cell(Cell::Dying(2), 4));
+    }
+    /// Tests a grid where every cell is alive joined into a torus,
+    /// so every cell counts its entire neighborhood, and bounded,
+    /// so the cells near the edges count less.
+    #[test]
+    fn full_torus_test() {
+        let rule: LargerThanLife = "R3,C0,M1,S49..49,B0..0".parse().unwrap();
+        let mut grid = DynGrid::new(10, 10).unwrap();
+        for y in 0..10 {
+            for x in 0..10 {
+                grid.set(x, y, Cell::Alive).unwrap();
+            }
+        }
+        grid.update();
+        rule.evolve(&mut grid, Topology::Torus);
+        assert_eq!(100, grid.population());
+        // Only the cells at least the range away from every edge have a full neighborhood.
+        rule.evolve(&mut grid, Topology::Bounded);
+        assert_eq!(16, grid.population());
+    }
+    proptest! {
+        /// Tests a rule parses back from the text it displays as.
+        #[test]
+        fn display_parse_round_trip_test(
+            range in 10usize..=MAX_RANGE,
+            states in 2u8..=255,
+            middle: bool,
+            von_neumann: bool,
+            survival in (0usize..100, 0usize..100),
+            birth in (0usize..100, 0usize..100),
+        ) {
+            let ordered = |(a, b): (usize, usize)| (a.min(b), a.max(b));
+            let rule = LargerThanLife { range, states, middle, survival: ordered(survival), birth: ordered(birth), von_neumann };
+            assert_eq!(Ok(rule), rule.to_string().parse());
+        }
+        /// Tests the counts taken from the table of sums are the same as
+        /// counting every neighbor of each cell, across every topology.
+        #[test]
+        fn naive_comparison_test(
+            cells in prop::collection::vec((0usize..13, 0usize..9), 0..80),
+            range in 1usize..6,
+            middle: bool,
+            von_neumann: bool,
+            survival in 0usize..8,
+            birth in 1usize..8,
+            states in 2u8..4,
+        ) {
+            let rule = LargerThanLife {
+                range,
+                states,
+                middle,
+                survival: (survival, survival + range * 3),
+                birth: (birth, birth + range * 2),
+                von_neumann,
+            };
+            for topology in ALL {
+                let mut grid = DynGrid::new(13, 9).unwrap();
+                for &(x, y) in &cells {
+                    grid.set(x, y, Cell::Alive).unwrap();
+                }
+                grid.update();
+                let mut expected = grid.clone();
+                for _ in 0..3 {
+                    rule.evolve(&mut grid, topology);
+                    evolve_with(&mut expected, &rule, topology, &rule.neighborhood());
+                }
+                assert_eq!(expected.cells(), grid.cells());
+            }
+        }
+        /// Tests a rule of range 1 counting the square without the middle is a Life-like rule.
+        #[test]
+        fn conway_test(cells in prop::collection::vec((0usize..12, 0usize..12), 0..60)) {
+            let rule: LargerThanLife = "R1,C0,M0,S2..3,B3..3,NM".parse().unwrap();
+            let mut grid = DynGrid::new(12, 12).unwrap();
+            for (x, y) in cells {
+                grid.set(x, y, Cell::Alive).unwrap();
+            }
+            grid.update();
+            let mut expected = grid.clone();
+            for _ in 0..4 {
+                rule.evolve(&mut grid, Topology::Torus);
+                evolve_each_cell(&mut expected, &Rule::CONWAY, Topology::Torus, &Neighborhood::Moore);
+            }
+            assert_eq!(expected.cells(), grid.cells());
+        }
+    }
+}
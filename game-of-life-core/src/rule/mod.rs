@@ -0,0 +1,600 @@
+use std::{fmt, str::FromStr};
+
+use crate::grid::{Cell, CellState};
+
+use self::hensel::{Configuration, LETTERS};
+
+pub mod hensel;
+pub mod larger_than_life;
+
+/// Decides the next state of a cell from its own state
+/// and the states of its neighbors, for automata whose cells are in the state `S`.
+pub trait Transition<S: CellState> {
+    /// Returns the state `cell` changes to during the next generation.
+    /// `neighbors` holds the state of each cell around it,
+    /// with cells off the edge of a bounded grid in the [quiescent](CellState::QUIESCENT) state.
+    fn next_state(&self, cell: S, neighbors: &[S]) -> S;
+}
+
+/// A Life-like rule, which decides the next state of a cell
+/// from the number of alive neighbors it has.
+/// Written as a rulestring such as `B3/S23`,
+/// cells are born (B) with 3 neighbors, and survive (S) with 2 or 3.
+///
+/// Rules of the Generations family have more than two states, written as `B2/S/C3`.
+/// An alive cell which does not survive passes through
+/// [`Cell::Dying`] states before it is dead, `C` counting the alive and dead states as well.
+///
+/// Isotropic non-totalistic rules also depend on how the alive neighbors are arranged,
+/// written in Hensel notation such as `B2-a/S12`, where letters after a count
+/// pick [configurations](hensel) of that many neighbors, and a `-` before them leaves them out.
+/// So `B2-a` births cells with two neighbors, unless they are next to each other and one is a corner.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Rule {
+    // Bit `n` is set when the configuration with index `n` applies,
+    // the configurations of each neighbor count being together in order.
+    birth: u64,
+    survival: u64,
+    states: u8,
+}
+
+/// The largest number of neighbors a cell can have.
+const MAX_NEIGHBORS: usize = 8;
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub const CONWAY: Rule = Rule::from_masks(0b1000, 0b1100);
+    /// HighLife, `B36/S23`.
+    pub const HIGHLIFE: Rule = Rule::from_masks(0b100_1000, 0b1100);
+    /// Seeds, `B2/S`.
+    pub const SEEDS: Rule = Rule::from_masks(0b100, 0);
+    /// Day & Night, `B3678/S34678`.
+    pub const DAY_AND_NIGHT: Rule = Rule::from_masks(0b1_1100_1000, 0b1_1101_1000);
+    /// Maze, `B3/S12345`.
+    pub const MAZE: Rule = Rule::from_masks(0b1000, 0b11_1110);
+    /// Brian's Brain, `B2/S/C3`.
+    pub const BRIANS_BRAIN: Rule = Rule::from_masks(0b100, 0).with_states(3);
+    /// Star Wars, `B2/S345/C4`.
+    pub const STAR_WARS: Rule = Rule::from_masks(0b100, 0b11_1000).with_states(4);
+
+    const fn from_masks(birth: u16, survival: u16) -> Self {
+        Rule { birth: Rule::configurations(birth), survival: Rule::configurations(survival), states: 2 }
+    }
+    /// Converts a mask of neighbor counts to a mask of every configuration with those counts.
+    const fn configurations(counts: u16) -> u64 {
+        let mut mask = 0;
+        let mut count = 0;
+        while count <= MAX_NEIGHBORS {
+            if counts & 1 << count != 0 {
+                mask |= Configuration::mask_of_count(count);
+            }
+            count += 1;
+        }
+        mask
+    }
+    const fn with_states(self, states: u8) -> Self {
+        Rule { states, ..self }
+    }
+    /// Creates a rule from the neighbor counts that cause
+    /// a dead cell to be born, and an alive cell to survive.
+    /// Returns Err if a count is greater than 8.
+    pub fn new(birth: &[usize], survival: &[usize]) -> Result<Self, ParseRuleError> {
+        Ok(Rule::from_masks(Rule::mask(birth)?, Rule::mask(survival)?))
+    }
+    /// Creates a rule of the Generations family, where cells have `states` states
+    /// counting alive and dead, so `states - 2` dying states.
+    /// Returns Err if a count is greater than 8, or there are less than 2 states.
+    pub fn generations(birth: &[usize], survival: &[usize], states: usize) -> Result<Self, ParseRuleError> {
+        Ok(Rule::new(birth, survival)?.with_states(Rule::states_from(states)?))
+    }
+    fn states_from(states: usize) -> Result<u8, ParseRuleError> {
+        match u8::try_from(states) {
+            Ok(states) if states >= 2 => Ok(states),
+            _ => Err(ParseRuleError::new(ParseRuleErrorKind::InvalidStates(states))),
+        }
+    }
+    fn mask(counts: &[usize]) -> Result<u16, ParseRuleError> {
+        counts.iter().try_fold(0, |mask, &count| {
+            if count > MAX_NEIGHBORS {
+                Err(ParseRuleError::new(ParseRuleErrorKind::InvalidCount(count)))
+            } else {
+                Ok(mask | 1 << count)
+            }
+        })
+    }
+    /// Returns true if a dead cell with `count` alive neighbors is born,
+    /// however the neighbors are arranged.
+    pub fn births(&self, count: usize) -> bool {
+        let all = Configuration::mask_of_count(count);
+        count <= MAX_NEIGHBORS && self.birth & all == all
+    }
+    /// Returns true if an alive cell with `count` alive neighbors survives,
+    /// however the neighbors are arranged.
+    pub fn survives(&self, count: usize) -> bool {
+        let all = Configuration::mask_of_count(count);
+        count <= MAX_NEIGHBORS && self.survival & all == all
+    }
+    /// Returns true if a dead cell whose alive neighbors are arranged in `configuration` is born.
+    pub fn births_in(&self, configuration: Configuration) -> bool {
+        self.birth & configuration.mask() != 0
+    }
+    /// Returns true if an alive cell whose alive neighbors are arranged in `configuration` survives.
+    pub fn survives_in(&self, configuration: Configuration) -> bool {
+        self.survival & configuration.mask() != 0
+    }
+    /// Returns true if only the number of alive neighbors matters,
+    /// not how they are arranged.
+    /// Such rules can be evolved by counting neighbors alone.
+    pub fn is_totalistic(&self) -> bool {
+        (0..=MAX_NEIGHBORS).all(|count| {
+            let all = Configuration::mask_of_count(count);
+            [self.birth & all, self.survival & all].iter().all(|&mask| mask == 0 || mask == all)
+        })
+    }
+    /// Returns the rule in the older `S/B` notation, ex. `23/3`,
+    /// with the number of states as a third section for Generations rules, ex. `345/2/4`.
+    pub fn to_survival_birth_string(&self) -> String {
+        let mut text = String::new();
+        // Writing to a `String` never fails.
+        write_configurations(&mut text, self.survival).unwrap();
+        text.push('/');
+        write_configurations(&mut text, self.birth).unwrap();
+        if self.states > 2 {
+            text += &format!("/{}", self.states);
+        }
+        text
+    }
+    /// Returns the number of states a cell can be in, counting alive and dead.
+    /// This is 2 unless the rule is of the Generations family.
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    /// Returns the state `cell` changes to during the next generation,
+    /// given it has `count` alive neighbors.
+    /// Under a rule which is not [totalistic](Rule::is_totalistic),
+    /// cells are only born or survive if they would with any arrangement of the neighbors,
+    /// use [`next_cell_in`](Rule::next_cell_in) to take the arrangement into account.
+    pub fn next_cell(&self, cell: Cell, count: usize) -> Cell {
+        self.next(cell, self.births(count), self.survives(count))
+    }
+    /// Returns the state `cell` changes to during the next generation,
+    /// given its alive neighbors are arranged in `configuration`.
+    pub fn next_cell_in(&self, cell: Cell, configuration: Configuration) -> Cell {
+        self.next(cell, self.births_in(configuration), self.survives_in(configuration))
+    }
+    fn next(&self, cell: Cell, births: bool, survives: bool) -> Cell {
+        match cell {
+            Cell::Dead if births => Cell::Alive,
+            Cell::Dead => Cell::Dead,
+            Cell::Alive if survives => Cell::Alive,
+            Cell::Alive => self.decay(0),
+            Cell::Dying(age) => self.decay(age),
+        }
+    }
+    /// Returns the state after a cell has been dying for `age` generations,
+    /// `0` being a cell which was just alive.
+    fn decay(&self, age: u8) -> Cell {
+        match age.checked_add(1) {
+            Some(age) if age <= self.states - 2 => Cell::Dying(age),
+            _ => Cell::Dead,
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+/// Eight neighbors are read as the Moore neighbors in the order of [`Configuration::of`],
+/// any other number of neighbors is only counted.
+/// [`evolve_each_cell`](crate::evolve_each_cell) only passes neighbors in that order,
+/// and counts the neighbors of any neighborhood which is not [Moore](crate::grid::neighborhood::Neighborhood::is_moore).
+impl Transition<Cell> for Rule {
+    fn next_state(&self, cell: Cell, neighbors: &[Cell]) -> Cell {
+        if neighbors.len() != MAX_NEIGHBORS {
+            return self.next_cell(cell, neighbors.iter().filter(|&&neighbor| neighbor == Cell::Alive).count());
+        }
+        let neighborhood = neighbors
+            .iter()
+            .enumerate()
+            .filter(|&(_, &neighbor)| neighbor == Cell::Alive)
+            .fold(0, |neighborhood, (i, _)| neighborhood | 1 << i);
+        self.next_cell_in(cell, Configuration::of(neighborhood))
+    }
+}
+
+impl fmt::Display for Rule {
+    /// Writes the rule in `B/S` notation, ex. `B3/S23`,
+    /// with the letters of Hensel notation when only some configurations of a count apply, ex. `B2-a/S12`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        write_configurations(f, self.birth)?;
+        write!(f, "/S")?;
+        write_configurations(f, self.survival)?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes each count with any of its configurations in `mask`.
+/// When only some of them are, the letters of those are written after the count,
+/// or a `-` and the letters of the rest when that is shorter.
+fn write_configurations(f: &mut impl fmt::Write, mask: u64) -> fmt::Result {
+    for (count, letters) in LETTERS.iter().enumerate() {
+        let all = Configuration::mask_of_count(count);
+        if mask & all == 0 {
+            continue;
+        }
+        write!(f, "{count}")?;
+        if mask & all == all {
+            continue;
+        }
+        let included = |letter: char| {
+            // Every letter in `LETTERS` names a configuration of its count.
+            let configuration = Configuration::new(count, Some(letter)).unwrap();
+            mask & configuration.mask() != 0
+        };
+        let (negated, letters): (bool, String) = if (mask & all).count_ones() as usize * 2 > letters.len() {
+            (true, letters.chars().filter(|&letter| !included(letter)).collect())
+        } else {
+            (false, letters.chars().filter(|&letter| included(letter)).collect())
+        };
+        if negated {
+            write!(f, "-")?;
+        }
+        write!(f, "{letters}")?;
+    }
+    Ok(())
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parses a rulestring in either `B/S` notation (`B3/S23`, `S23/B3`),
+    /// or the older `S/B` notation without letters (`23/3`).
+    /// Either can use Hensel notation for the configurations of each count, ex. `B2-a/S12`.
+    /// Generations rules have a third section with the number of states,
+    /// `B2/S/C3` in `B/S` notation or `/2/3` in `S/B` notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sections: Vec<&str> = s.trim().split('/').collect();
+        let (first, second, states) = match sections[..] {
+            [_] => return Err(ParseRuleError::new(ParseRuleErrorKind::MissingSeparator)),
+            [first, second] => (first, second, None),
+            [first, second, states] => (first, second, Some(states)),
+            _ => return Err(ParseRuleError::new(ParseRuleErrorKind::TooManySections)),
+        };
+        let (first, second) = (Section::parse(first)?, Section::parse(second)?);
+        let labeled = !matches!(first, Section::Unlabeled(_));
+        let states = match states {
+            Some(states) => Rule::states_from(parse_states(states, labeled)?)?,
+            None => 2,
+        };
+        let (birth, survival) = match (first, second) {
+            (Section::Birth(birth), Section::Survival(survival))
+            | (Section::Survival(survival), Section::Birth(birth)) => (birth, survival),
+            // Old notation lists survival first.
+            (Section::Unlabeled(survival), Section::Unlabeled(birth)) => (birth, survival),
+            (Section::Birth(_), Section::Birth(_))
+            | (Section::Survival(_), Section::Survival(_)) => {
+                return Err(ParseRuleError::new(ParseRuleErrorKind::DuplicateSection));
+            },
+            _ => return Err(ParseRuleError::new(ParseRuleErrorKind::MixedNotation)),
+        };
+        Ok(Rule { birth, survival, states })
+    }
+}
+
+/// Parses the number of states of a Generations rule,
+/// which is labeled with `C` in `B/S` notation.
+fn parse_states(s: &str, labeled: bool) -> Result<usize, ParseRuleError> {
+    let digits = match (s.strip_prefix(['C', 'c']), labeled) {
+        (Some(digits), true) => digits,
+        (None, false) => s,
+        _ => return Err(ParseRuleError::new(ParseRuleErrorKind::MixedNotation)),
+    };
+    if digits.is_empty() {
+        return Err(ParseRuleError::new(ParseRuleErrorKind::InvalidStates(0)));
+    }
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_digit()) {
+        return Err(ParseRuleError::new(ParseRuleErrorKind::InvalidCharacter(c)));
+    }
+    // Counts too large for `usize` are still too many states.
+    Ok(digits.parse().unwrap_or(usize::MAX))
+}
+
+/// One side of the `/` in a rulestring,
+/// holding a mask of configurations like [`Rule`].
+enum Section {
+    Birth(u64),
+    Survival(u64),
+    Unlabeled(u64),
+}
+
+impl Section {
+    fn parse(s: &str) -> Result<Self, ParseRuleError> {
+        let mut chars = s.chars();
+        let section: fn(u64) -> Section = match chars.clone().next() {
+            Some('B' | 'b') => {
+                chars.next();
+                Section::Birth
+            },
+            Some('S' | 's') => {
+                chars.next();
+                Section::Survival
+            },
+            _ => Section::Unlabeled,
+        };
+        let mut chars = chars.peekable();
+        let mut mask = 0;
+        while let Some(c) = chars.next() {
+            let count = match c.to_digit(10) {
+                Some(count) if count as usize <= MAX_NEIGHBORS => count as usize,
+                _ => return Err(ParseRuleError::new(ParseRuleErrorKind::InvalidCharacter(c))),
+            };
+            let negated = chars.next_if_eq(&'-').is_some();
+            let mut letters = 0;
+            // Only letters used by Hensel notation are read,
+            // so anything else is reported as an invalid character.
+            while let Some(letter) = chars.next_if(|&c| LETTERS[4].contains(c)) {
+                match Configuration::new(count, Some(letter)) {
+                    Some(configuration) => letters |= configuration.mask(),
+                    None => return Err(ParseRuleError::new(ParseRuleErrorKind::InvalidConfiguration(count, letter))),
+                }
+            }
+            let all = Configuration::mask_of_count(count);
+            mask |= match (negated, letters) {
+                (false, 0) => all,
+                (false, _) => letters,
+                (true, 0) => return Err(ParseRuleError::new(ParseRuleErrorKind::InvalidCharacter('-'))),
+                (true, _) => all & !letters,
+            };
+        }
+        Ok(section(mask))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct ParseRuleError {
+    kind: ParseRuleErrorKind,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ParseRuleErrorKind {
+    /// The rulestring has no `/` between birth and survival.
+    MissingSeparator,
+    /// The rulestring has more than three sections.
+    TooManySections,
+    /// Both sections are birth, or both are survival.
+    DuplicateSection,
+    /// One section is labeled with `B` or `S` and the other is not.
+    MixedNotation,
+    /// A character which is not a neighbor count from 0 to 8.
+    InvalidCharacter(char),
+    /// A neighbor count greater than 8.
+    InvalidCount(usize),
+    /// A letter which does not name a configuration of the neighbor count before it.
+    InvalidConfiguration(usize, char),
+    /// A number of states less than 2 or greater than 255.
+    InvalidStates(usize),
+}
+
+impl ParseRuleError {
+    pub(crate) fn new(kind: ParseRuleErrorKind) -> Self {
+        ParseRuleError { kind }
+    }
+    pub fn kind(&self) -> ParseRuleErrorKind { self.kind }
+}
+impl std::error::Error for ParseRuleError {}
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseRuleErrorKind::MissingSeparator => write!(f, "rule is missing a '/' separator"),
+            ParseRuleErrorKind::TooManySections => write!(f, "rule has too many '/' separated sections"),
+            ParseRuleErrorKind::DuplicateSection => write!(f, "rule has the same section twice"),
+            ParseRuleErrorKind::MixedNotation => write!(f, "rule mixes B/S and S/B notation"),
+            ParseRuleErrorKind::InvalidCharacter(c) => write!(f, "'{c}' is not a neighbor count from 0 to 8"),
+            ParseRuleErrorKind::InvalidCount(count) => write!(f, "{count} is not a neighbor count from 0 to 8"),
+            ParseRuleErrorKind::InvalidConfiguration(count, letter) => {
+                write!(f, "'{letter}' is not a configuration of {count} neighbors")
+            },
+            ParseRuleErrorKind::InvalidStates(states) => write!(f, "{states} is not a number of states from 2 to 255"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn default_rule_test() {
+        assert_eq!(Rule::CONWAY, Rule::default());
+    }
+    #[test]
+    fn parse_named_rules_test() {
+        assert_eq!(Ok(Rule::CONWAY), "B3/S23".parse());
+        assert_eq!(Ok(Rule::HIGHLIFE), "B36/S23".parse());
+        assert_eq!(Ok(Rule::SEEDS), "B2/S".parse());
+        assert_eq!(Ok(Rule::DAY_AND_NIGHT), "B3678/S34678".parse());
+        assert_eq!(Ok(Rule::MAZE), "B3/S12345".parse());
+    }
+    #[test]
+    fn parse_notations_test() {
+        assert_eq!(Ok(Rule::CONWAY), "b3/s23".parse());
+        assert_eq!(Ok(Rule::CONWAY), "S23/B3".parse());
+        assert_eq!(Ok(Rule::CONWAY), "23/3".parse());
+        assert_eq!(Ok(Rule::CONWAY), " B3/S23\n".parse());
+        assert_eq!(Ok(Rule::SEEDS), "/2".parse());
+    }
+    #[test]
+    fn parse_error_test() {
+        let kind = |s: &str| s.parse::<Rule>().unwrap_err().kind();
+        assert_eq!(ParseRuleErrorKind::MissingSeparator, kind("B3S23"));
+        assert_eq!(ParseRuleErrorKind::TooManySections, kind("B3/S23/C2/1"));
+        assert_eq!(ParseRuleErrorKind::DuplicateSection, kind("B3/B23"));
+        assert_eq!(ParseRuleErrorKind::DuplicateSection, kind("S3/S23"));
+        assert_eq!(ParseRuleErrorKind::MixedNotation, kind("B3/23"));
+        assert_eq!(ParseRuleErrorKind::MixedNotation, kind("23/B3"));
+        assert_eq!(ParseRuleErrorKind::InvalidCharacter('9'), kind("B39/S23"));
+        assert_eq!(ParseRuleErrorKind::InvalidCharacter('x'), kind("B3/S2x"));
+        assert_eq!(ParseRuleErrorKind::MixedNotation, kind("B2/S/3"));
+        assert_eq!(ParseRuleErrorKind::MixedNotation, kind("/2/C3"));
+        assert_eq!(ParseRuleErrorKind::InvalidCharacter('x'), kind("B2/S/C3x"));
+        assert_eq!(ParseRuleErrorKind::InvalidStates(1), kind("B2/S/C1"));
+        assert_eq!(ParseRuleErrorKind::InvalidStates(256), kind("B2/S/C256"));
+        assert_eq!(ParseRuleErrorKind::InvalidStates(0), kind("B2/S/C"));
+    }
+    #[test]
+    fn parse_generations_test() {
+        assert_eq!(Ok(Rule::BRIANS_BRAIN), "B2/S/C3".parse());
+        assert_eq!(Ok(Rule::BRIANS_BRAIN), "/2/3".parse());
+        assert_eq!(Ok(Rule::STAR_WARS), "345/2/4".parse());
+        assert_eq!(Ok(Rule::STAR_WARS), "S345/B2/C4".parse());
+        assert_eq!(Ok(Rule::CONWAY), "B3/S23/C2".parse());
+        assert_eq!(3, Rule::BRIANS_BRAIN.states());
+        assert_eq!(2, Rule::CONWAY.states());
+    }
+    #[test]
+    fn parse_hensel_test() {
+        let rule: Rule = "B2-a/S12".parse().unwrap();
+        assert!(!rule.is_totalistic());
+        assert!(!rule.births(2));
+        assert!(rule.births_in(Configuration::new(2, Some('e')).unwrap()));
+        assert!(!rule.births_in(Configuration::new(2, Some('a')).unwrap()));
+        assert!(rule.survives(1));
+        assert!(rule.survives(2));
+        assert_eq!(Ok(rule), "B2cekin/S12".parse());
+        assert_eq!(Ok(rule), "12/2-a".parse());
+        // Naming every letter of a count is the same as the count alone.
+        assert_eq!(Ok(Rule::CONWAY), "B3/S2cekain3".parse());
+        assert_eq!("B3/S2c3".parse::<Rule>(), "B3/S2ccc3".parse());
+        let tlife: Rule = "B3/S2-i34q".parse().unwrap();
+        assert!(tlife.survives_in(Configuration::new(4, Some('q')).unwrap()));
+        assert!(!tlife.survives_in(Configuration::new(4, Some('a')).unwrap()));
+        assert!(!tlife.survives_in(Configuration::new(2, Some('i')).unwrap()));
+        assert!(tlife.survives(3));
+    }
+    #[test]
+    fn parse_hensel_error_test() {
+        let kind = |s: &str| s.parse::<Rule>().unwrap_err().kind();
+        assert_eq!(ParseRuleErrorKind::InvalidConfiguration(2, 'y'), kind("B2y/S23"));
+        assert_eq!(ParseRuleErrorKind::InvalidConfiguration(1, 'a'), kind("B3/S1a"));
+        assert_eq!(ParseRuleErrorKind::InvalidConfiguration(0, 'c'), kind("B0c/S"));
+        assert_eq!(ParseRuleErrorKind::InvalidConfiguration(8, 'e'), kind("B3/S8-e"));
+        assert_eq!(ParseRuleErrorKind::InvalidCharacter('-'), kind("B2-/S23"));
+        assert_eq!(ParseRuleErrorKind::InvalidCharacter('-'), kind("B-a/S23"));
+        assert_eq!(ParseRuleErrorKind::InvalidCharacter('x'), kind("B2ax/S23"));
+    }
+    #[test]
+    fn is_totalistic_test() {
+        assert!(Rule::CONWAY.is_totalistic());
+        assert!(Rule::STAR_WARS.is_totalistic());
+        assert!(!"B3/S2-i34q".parse::<Rule>().unwrap().is_totalistic());
+        assert!(!"B3a/S23".parse::<Rule>().unwrap().is_totalistic());
+    }
+    #[test]
+    fn new_test() {
+        assert_eq!(Ok(Rule::HIGHLIFE), Rule::new(&[3, 6], &[2, 3]));
+        assert_eq!(ParseRuleErrorKind::InvalidCount(9), Rule::new(&[9], &[]).unwrap_err().kind());
+        assert_eq!(Ok(Rule::STAR_WARS), Rule::generations(&[2], &[3, 4, 5], 4));
+        assert_eq!(ParseRuleErrorKind::InvalidStates(300), Rule::generations(&[2], &[], 300).unwrap_err().kind());
+    }
+    #[test]
+    fn display_test() {
+        assert_eq!("B3/S23", Rule::CONWAY.to_string());
+        assert_eq!("B2/S", Rule::SEEDS.to_string());
+        assert_eq!("B3678/S34678", Rule::DAY_AND_NIGHT.to_string());
+        assert_eq!("B2/S/C3", Rule::BRIANS_BRAIN.to_string());
+        assert_eq!("B2/S345/C4", Rule::STAR_WARS.to_string());
+        assert_eq!("B2-a/S12", "B2cekin/S12".parse::<Rule>().unwrap().to_string());
+        assert_eq!("B3/S2-i34q", "S2cekan34q/B3".parse::<Rule>().unwrap().to_string());
+        assert_eq!("B2ce3-k/S/C3", "B2ec3ceainyqjr/S/C3".parse::<Rule>().unwrap().to_string());
+        assert_eq!("23/3", Rule::CONWAY.to_survival_birth_string());
+        assert_eq!("/2/3", Rule::BRIANS_BRAIN.to_survival_birth_string());
+        assert_eq!("345/2/4", Rule::STAR_WARS.to_survival_birth_string());
+        assert_eq!("2-i34q/3", "B3/S2-i34q".parse::<Rule>().unwrap().to_survival_birth_string());
+    }
+    #[test]
+    fn display_error_test() {
+        let e = ParseRuleError::new(ParseRuleErrorKind::InvalidCharacter('9'));
+        assert_eq!("'9' is not a neighbor count from 0 to 8", e.to_string());
+        let e = ParseRuleError::new(ParseRuleErrorKind::InvalidConfiguration(2, 'y'));
+        assert_eq!("'y' is not a configuration of 2 neighbors", e.to_string());
+    }
+    #[test]
+    fn conway_next_cell_test() {
+        for count in 0..=MAX_NEIGHBORS {
+            let born = if count == 3 { Cell::Alive } else { Cell::Dead };
+            assert_eq!(born, Rule::CONWAY.next_cell(Cell::Dead, count));
+            let survived = if count == 2 || count == 3 { Cell::Alive } else { Cell::Dead };
+            assert_eq!(survived, Rule::CONWAY.next_cell(Cell::Alive, count));
+        }
+    }
+    /// Tests a cell which does not survive passes through
+    /// every dying state before it is dead, and can not be born while dying.
+    #[test]
+    fn star_wars_next_cell_test() {
+        let rule = Rule::STAR_WARS;
+        assert_eq!(Cell::Alive, rule.next_cell(Cell::Alive, 3));
+        assert_eq!(Cell::Dying(1), rule.next_cell(Cell::Alive, 2));
+        assert_eq!(Cell::Dying(2), rule.next_cell(Cell::Dying(1), 2));
+        assert_eq!(Cell::Dead, rule.next_cell(Cell::Dying(2), 2));
+        assert_eq!(Cell::Alive, rule.next_cell(Cell::Dead, 2));
+        // A state left over from a rule with more states dies.
+        assert_eq!(Cell::Dead, rule.next_cell(Cell::Dying(200), 2));
+        assert_eq!(Cell::Dead, Rule::CONWAY.next_cell(Cell::Dying(1), 3));
+    }
+    /// Tests cells only survive in the configurations the rule names,
+    /// and counting alone only keeps cells which survive in all of them.
+    #[test]
+    fn next_cell_in_test() {
+        let rule: Rule = "B3/S2-i34q".parse().unwrap();
+        for configuration in Configuration::all() {
+            let expected = match configuration.to_string().as_str() {
+                "2i" | "4c" | "4e" | "4k" | "4a" | "4i" | "4n" | "4y" | "4j" | "4r" | "4t" | "4w" | "4z" => Cell::Dead,
+                name if name.starts_with(['2', '3', '4']) => Cell::Alive,
+                _ => Cell::Dead,
+            };
+            assert_eq!(expected, rule.next_cell_in(Cell::Alive, configuration), "{configuration}");
+        }
+        assert_eq!(Cell::Dead, rule.next_cell(Cell::Alive, 2));
+        assert_eq!(Cell::Alive, rule.next_cell(Cell::Alive, 3));
+    }
+    proptest! {
+        /// Tests a rule parses back from the text it displays as.
+        #[test]
+        fn display_parse_round_trip_test(birth in 0u16..1 << 9, survival in 0u16..1 << 9, states in 2u8..=255) {
+            let rule = Rule::from_masks(birth, survival).with_states(states);
+            assert_eq!(Ok(rule), rule.to_string().parse());
+        }
+        /// Tests a rule naming any configurations parses back from the text it displays as.
+        #[test]
+        fn hensel_round_trip_test(birth in 0u64..1 << hensel::CONFIGURATIONS, survival in 0u64..1 << hensel::CONFIGURATIONS) {
+            let rule = Rule { birth, survival, states: 2 };
+            assert_eq!(Ok(rule), rule.to_string().parse());
+        }
+        /// Tests a rule of any number of states, naming any configurations,
+        /// parses back from the text it is written as in `S/B` notation.
+        #[test]
+        fn survival_birth_round_trip_test(
+            birth in 0u64..1 << hensel::CONFIGURATIONS,
+            survival in 0u64..1 << hensel::CONFIGURATIONS,
+            states in 2u8..=255,
+        ) {
+            let rule = Rule { birth, survival, states };
+            assert_eq!(Ok(rule), rule.to_survival_birth_string().parse());
+        }
+        /// Tests counts above the largest neighbor count never birth nor survive.
+        #[test]
+        fn out_of_range_count_test(count in MAX_NEIGHBORS + 1..usize::MAX) {
+            let rule = Rule::from_masks(u16::MAX, u16::MAX);
+            assert!(!rule.births(count));
+            assert!(!rule.survives(count));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Games where births, survivals and updates only happen by chance,
+//! driven by the seeded [`Rng`] each [`GameOfLife`] owns.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{grid::Cell, random::Rng, GameOfLife, Universe};
+
+/// The probabilities that what the rule decides for a cell actually happens.
+/// Each is from 0 to 1, and the game follows its rule exactly when all are 1, as by default.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Stochastic {
+    /// The probability a dead cell the rule births is born.
+    pub birth: f64,
+    /// The probability an alive cell the rule keeps alive survives,
+    /// otherwise it dies as if the rule killed it.
+    pub survival: f64,
+    /// The probability a cell updates at all during a generation,
+    /// otherwise it keeps its state, so only a fraction of the cells update at once.
+    pub update: f64,
+}
+
+impl Default for Stochastic {
+    fn default() -> Self {
+        Stochastic { birth: 1.0, survival: 1.0, update: 1.0 }
+    }
+}
+
+impl Stochastic {
+    /// Returns true if every probability is certain, so nothing is left to chance.
+    pub fn is_deterministic(&self) -> bool {
+        self.birth >= 1.0 && self.survival >= 1.0 && self.update >= 1.0
+    }
+}
+
+impl<T: Universe> GameOfLife<T> {
+    /// Returns the game leaving births, survivals and updates to chance with the `stochastic` probabilities.
+    pub fn with_stochastic(mut self, stochastic: Stochastic) -> Self {
+        self.stochastic = stochastic;
+        self
+    }
+    /// Returns the game drawing its random numbers from `seed`,
+    /// so a game with the same seed, grid and settings always plays out the same.
+    /// The seed is 0 by default.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.set_seed(seed);
+        self
+    }
+    pub fn stochastic(&self) -> Stochastic {
+        self.stochastic
+    }
+    pub fn set_stochastic(&mut self, stochastic: Stochastic) {
+        self.stochastic = stochastic;
+    }
+    /// Restarts the random numbers of the game from `seed`.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    /// Evolves the universe under the rule, then leaves each cell which could have changed to chance.
+    /// Cells are visited in order of their coordinates, so the same seed draws the same numbers for each cell.
+    pub(crate) fn evolve_stochastically(&mut self) {
+        let before: BTreeMap<(i64, i64), Cell> = self.grid.cells().into_iter().collect();
+        self.grid.evolve(&self.rule, self.topology, &self.neighborhood);
+        let after: BTreeMap<(i64, i64), Cell> = self.grid.cells().into_iter().collect();
+        let death = if self.rule.states() > 2 { Cell::Dying(1) } else { Cell::Dead };
+        let positions: BTreeSet<(i64, i64)> = before.keys().chain(after.keys()).copied().collect();
+        let mut changes = Vec::new();
+        for position in positions {
+            let old = before.get(&position).copied().unwrap_or(Cell::Dead);
+            let new = after.get(&position).copied().unwrap_or(Cell::Dead);
+            let chosen = if !self.rng.chance(self.stochastic.update) {
+                old
+            } else {
+                match (old, new) {
+                    (Cell::Dead, Cell::Alive) if !self.rng.chance(self.stochastic.birth) => Cell::Dead,
+                    (Cell::Alive, Cell::Alive) if !self.rng.chance(self.stochastic.survival) => death,
+                    _ => new,
+                }
+            };
+            if chosen != new {
+                changes.push((position, chosen));
+            }
+        }
+        self.grid.set_cells(&changes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::proptest;
+    use crate::{grid::{dynamic::DynGrid, Grid}, rule::Rule};
+
+    #[test]
+    fn default_test() {
+        assert!(Stochastic::default().is_deterministic());
+        assert!(!Stochastic { birth: 0.5, ..Stochastic::default() }.is_deterministic());
+        let game = GameOfLife::from_grid(DynGrid::new(4, 4).unwrap());
+        assert_eq!(Stochastic::default(), game.stochastic());
+        assert_eq!(Rng::new(0), game.rng);
+    }
+    /// Tests a cell which fails to survive under a Generations rule starts dying.
+    #[test]
+    fn generations_death_test() {
+        let mut game = GameOfLife::from_grid(DynGrid::new(4, 4).unwrap())
+            .with_rule(Rule::STAR_WARS)
+            .unwrap()
+            .with_stochastic(Stochastic { survival: 0.0, ..Stochastic::default() });
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+            game.grid_mut().set(x, y, Cell::Alive).unwrap();
+        }
+        game.grid_mut().update();
+        game.next();
+        for (x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)] {
+            assert_eq!(Ok(Cell::Dying(1)), game.grid().get(x, y));
+        }
+    }
+    proptest! {
+        /// Tests a cell is never born when births never happen,
+        /// so alive cells can only come from cells which were alive before.
+        #[test]
+        fn no_birth_test(cells in proptest::collection::vec((0usize..10, 0usize..10), 0..40), seed: u64) {
+            let mut game = GameOfLife::from_grid(DynGrid::new(10, 10).unwrap())
+                .with_stochastic(Stochastic { birth: 0.0, survival: 0.5, update: 1.0 })
+                .with_seed(seed);
+            for (x, y) in cells {
+                game.grid_mut().set(x, y, Cell::Alive).unwrap();
+            }
+            game.grid_mut().update();
+            for _ in 0..5 {
+                let before = game.grid().cells();
+                game.next();
+                for cell in game.grid().cells() {
+                    assert!(before.contains(&cell));
+                }
+            }
+        }
+    }
+}
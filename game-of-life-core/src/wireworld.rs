@@ -0,0 +1,168 @@
+//! Wireworld, an automaton for modelling electronic circuits.
+//!
+//! Electrons travel along conductors as a head followed by a tail.
+//! Each generation, heads become tails, tails become conductors again,
+//! and a conductor becomes a head if one or two of its neighbors are heads.
+//! Grids of [`Wire`]s are evolved with [`evolve_with`](crate::evolve_with),
+//! usually in the Moore neighborhood.
+
+use crate::{grid::CellState, rule::Transition};
+
+/// The state of a cell in Wireworld.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub enum Wire {
+    Empty,
+    /// The front of an electron.
+    Head,
+    /// The back of an electron, which stops it from turning around.
+    Tail,
+    Conductor,
+}
+
+impl CellState for Wire {
+    const QUIESCENT: Wire = Wire::Empty;
+}
+
+/// The rules of Wireworld.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Wireworld;
+
+impl Transition<Wire> for Wireworld {
+    fn next_state(&self, cell: Wire, neighbors: &[Wire]) -> Wire {
+        match cell {
+            Wire::Empty => Wire::Empty,
+            Wire::Head => Wire::Tail,
+            Wire::Tail => Wire::Conductor,
+            Wire::Conductor => match neighbors.iter().filter(|&&neighbor| neighbor == Wire::Head).count() {
+                1 | 2 => Wire::Head,
+                _ => Wire::Conductor,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::{evolve_with, grid::{dynamic::DynGrid, neighborhood::Neighborhood, obj::GameOfLifeGrid, topology::Topology, Grid}};
+
+    /// A diode, which lets electrons through from left to right only.
+    const DIODE: [&str; 3] = [
+        "......##......",
+        "#######.######",
+        "......##......",
+    ];
+    /// A loop of six cells with one electron going around it,
+    /// sending an electron down the wire on its right every six generations.
+    const CLOCK: [&str; 3] = [
+        ".H#.....",
+        "t..#####",
+        ".##.....",
+    ];
+
+    #[test]
+    fn new_grid_is_empty_test() {
+        let grid = GameOfLifeGrid::<4, 4, Wire>::default();
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(Ok(Wire::Empty), grid.get(x, y));
+            }
+        }
+    }
+    /// Tests an electron sent from the left
+    /// comes out of the right end of the diode.
+    #[test]
+    fn diode_forward_test() {
+        let mut grid = circuit(&DIODE);
+        grid.set(0, 1, Wire::Tail).unwrap();
+        grid.set(1, 1, Wire::Head).unwrap();
+        grid.update();
+        let arrivals = heads_at(&mut grid, 13, 1, 20);
+        assert_eq!(vec![12], arrivals);
+    }
+    /// Tests an electron sent from the right
+    /// never reaches the left end of the diode,
+    /// leaving nothing but conductors behind.
+    #[test]
+    fn diode_backward_test() {
+        let mut grid = circuit(&DIODE);
+        grid.set(13, 1, Wire::Tail).unwrap();
+        grid.set(12, 1, Wire::Head).unwrap();
+        grid.update();
+        let arrivals = heads_at(&mut grid, 0, 1, 20);
+        assert!(arrivals.is_empty());
+        assert_eq!(wires(&circuit(&DIODE), 14), wires(&grid, 14));
+    }
+    /// Tests the clock sends an electron to the end of its wire
+    /// every six generations, with its loop returning to where it started each time.
+    #[test]
+    fn clock_test() {
+        let mut grid = circuit(&CLOCK);
+        let arrivals = heads_at(&mut grid, 7, 1, 36);
+        assert_eq!(vec![6, 12, 18, 24, 30, 36], arrivals);
+        // The loop is the first four columns,
+        // the rest is the wire it sends electrons down.
+        assert_eq!(wires(&circuit(&CLOCK), 4), wires(&grid, 4));
+    }
+    proptest! {
+        /// Tests heads always become tails, tails always become conductors,
+        /// and empty cells stay empty, whatever their neighbors are.
+        #[test]
+        fn fixed_transitions_test(neighbors in prop::collection::vec(wire(), 8)) {
+            assert_eq!(Wire::Tail, Wireworld.next_state(Wire::Head, &neighbors));
+            assert_eq!(Wire::Conductor, Wireworld.next_state(Wire::Tail, &neighbors));
+            assert_eq!(Wire::Empty, Wireworld.next_state(Wire::Empty, &neighbors));
+        }
+        /// Tests a conductor becomes a head with exactly one or two head neighbors.
+        #[test]
+        fn conductor_transition_test(neighbors in prop::collection::vec(wire(), 8)) {
+            let heads = neighbors.iter().filter(|&&neighbor| neighbor == Wire::Head).count();
+            let expected = if heads == 1 || heads == 2 { Wire::Head } else { Wire::Conductor };
+            assert_eq!(expected, Wireworld.next_state(Wire::Conductor, &neighbors));
+        }
+    }
+
+    // Helper Functions
+    // These are called in tests above.
+
+    fn wire() -> impl Strategy<Value = Wire> {
+        prop_oneof![Just(Wire::Empty), Just(Wire::Head), Just(Wire::Tail), Just(Wire::Conductor)]
+    }
+    /// Builds a grid from rows of text,
+    /// where `#` is a conductor, `H` a head, `t` a tail, and anything else is empty.
+    fn circuit(rows: &[&str]) -> DynGrid<Wire> {
+        let mut grid = DynGrid::new(rows[0].len(), rows.len()).unwrap();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let wire = match c {
+                    '#' => Wire::Conductor,
+                    'H' => Wire::Head,
+                    't' => Wire::Tail,
+                    _ => Wire::Empty,
+                };
+                grid.set(x, y, wire).unwrap();
+            }
+        }
+        grid.update();
+        grid
+    }
+    /// Returns the state of every cell in the first `width` columns, row by row.
+    fn wires(grid: &DynGrid<Wire>, width: usize) -> Vec<Wire> {
+        (0..grid.height())
+            .flat_map(|y| (0..width).map(move |x| grid.get(x, y).unwrap()))
+            .collect()
+    }
+    /// Runs the circuit for `generations` generations,
+    /// returning each generation the cell at `(x, y)` was a head.
+    fn heads_at(grid: &mut DynGrid<Wire>, x: usize, y: usize, generations: u64) -> Vec<u64> {
+        let mut arrivals = Vec::new();
+        for generation in 1..=generations {
+            evolve_with(grid, &Wireworld, Topology::Bounded, &Neighborhood::Moore);
+            if grid.get(x, y) == Ok(Wire::Head) {
+                arrivals.push(generation);
+            }
+        }
+        arrivals
+    }
+}
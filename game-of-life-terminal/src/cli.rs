@@ -0,0 +1,252 @@
+//! Command-line arguments of the terminal.
+
+use std::{path::PathBuf, time::Duration};
+
+use game_of_life_core::{
+    grid::{neighborhood::Neighborhood, topology::Topology},
+    rule::{larger_than_life::MAX_RANGE, Rule},
+};
+
+pub const USAGE: &str = "\
+Usage: game-of-life-terminal [OPTIONS]
+
+Options:
+  --width <CELLS>        Width of the grid [default: 12]
+  --height <CELLS>       Height of the grid [default: 12]
+  --rule <RULE>          Rule of the game, ex. B36/S23 [default: the pattern's rule, or B3/S23]
+  --pattern <FILE>       Pattern to start with, centered on the grid.
+                         .cells and .txt files are plaintext, .lif and .life files are Life 1.05/1.06,
+                         any other file is RLE
+  --topology <TOPOLOGY>  How the edges of the grid join: bounded, torus, cylinder,
+                         klein-bottle or cross-surface [default: bounded]
+  --neighborhood <NEIGHBORHOOD>
+                         Which cells are neighbors: moore, von-neumann, hexagonal,
+                         extended-moore:<RANGE> or extended-von-neumann:<RANGE>,
+                         where the range is from 1 to 500 [default: moore]
+  --generations <N>      Generations to run before showing the grid [default: 0]
+  --delay <MS>           Milliseconds between generations while playing [default: 200]
+  --headless             Print the grid after running the generations, then exit
+  --output <OUTPUT>      What --headless prints: grid or population [default: grid]
+  -h, --help             Print this help
+";
+
+/// What `--headless` prints once the generations have run.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Output {
+    Grid,
+    Population,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub width: usize,
+    pub height: usize,
+    pub rule: Option<Rule>,
+    pub pattern: Option<PathBuf>,
+    pub topology: Topology,
+    pub neighborhood: Neighborhood,
+    pub generations: u64,
+    pub delay: Duration,
+    pub headless: bool,
+    pub output: Output,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            width: 12,
+            height: 12,
+            rule: None,
+            pattern: None,
+            topology: Topology::Bounded,
+            neighborhood: Neighborhood::Moore,
+            generations: 0,
+            delay: Duration::from_millis(200),
+            headless: false,
+            output: Output::Grid,
+            help: false,
+        }
+    }
+}
+
+/// Parses the arguments, not including the program name.
+/// Values can follow their flag either as the next argument or after `=`,
+/// ex. `--width 80` or `--width=80`.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = || inline.clone().or_else(|| args.next()).ok_or(format!("{flag} needs a value"));
+        match flag.as_str() {
+            "--width" => options.width = size(&flag, &value()?)?,
+            "--height" => options.height = size(&flag, &value()?)?,
+            "--rule" => {
+                let rule = value()?;
+                options.rule = Some(rule.parse().map_err(|e| format!("invalid rule '{rule}': {e}"))?);
+            },
+            "--pattern" => options.pattern = Some(PathBuf::from(value()?)),
+            "--topology" => options.topology = topology(&value()?)?,
+            "--neighborhood" => options.neighborhood = neighborhood(&value()?)?,
+            "--generations" => options.generations = number(&flag, &value()?)?,
+            "--delay" => options.delay = Duration::from_millis(number(&flag, &value()?)?),
+            "--headless" => options.headless = true,
+            "--output" => {
+                options.output = match value()?.as_str() {
+                    "grid" => Output::Grid,
+                    "population" => Output::Population,
+                    output => return Err(format!("invalid output '{output}', expected grid or population")),
+                }
+            },
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("unknown argument '{flag}'")),
+        }
+    }
+    Ok(options)
+}
+
+fn number(flag: &str, value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| format!("{flag} expects a whole number, got '{value}'"))
+}
+
+/// Parses a width or height, which can not be 0.
+fn size(flag: &str, value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("{flag} expects a number of cells above 0, got '{value}'")),
+        Ok(size) => Ok(size),
+    }
+}
+
+fn topology(value: &str) -> Result<Topology, String> {
+    match value {
+        "bounded" => Ok(Topology::Bounded),
+        "torus" => Ok(Topology::Torus),
+        "cylinder" => Ok(Topology::Cylinder),
+        "klein-bottle" => Ok(Topology::KleinBottle),
+        "cross-surface" => Ok(Topology::CrossSurface),
+        _ => Err(format!("invalid topology '{value}', expected bounded, torus, cylinder, klein-bottle or cross-surface")),
+    }
+}
+
+/// Parses a neighborhood, where the extended neighborhoods
+/// are given with their range, ex. `extended-moore:2`,
+/// up to the largest range of Larger than Life rules.
+fn neighborhood(value: &str) -> Result<Neighborhood, String> {
+    let extended = |range: &str, neighborhood: fn(usize) -> Neighborhood| match range.parse() {
+        Ok(range) if (1..=MAX_RANGE).contains(&range) => Ok(neighborhood(range)),
+        _ => Err(format!("invalid range '{range}' in '{value}', expected a whole number from 1 to {MAX_RANGE}")),
+    };
+    match value.split_once(':') {
+        None if value == "moore" => Ok(Neighborhood::Moore),
+        None if value == "von-neumann" => Ok(Neighborhood::VonNeumann),
+        None if value == "hexagonal" => Ok(Neighborhood::Hexagonal),
+        Some(("extended-moore", range)) => extended(range, Neighborhood::ExtendedMoore),
+        Some(("extended-von-neumann", range)) => extended(range, Neighborhood::ExtendedVonNeumann),
+        _ => Err(format!(
+            "invalid neighborhood '{value}', expected moore, von-neumann, hexagonal, \
+             extended-moore:<RANGE> or extended-von-neumann:<RANGE>"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_defaults_test() {
+        let options = parse(args(&[])).unwrap();
+        assert_eq!((12, 12), (options.width, options.height));
+        assert_eq!(None, options.rule);
+        assert_eq!(Topology::Bounded, options.topology);
+        assert_eq!(Neighborhood::Moore, options.neighborhood);
+        assert_eq!(Output::Grid, options.output);
+        assert!(!options.headless);
+        assert!(!options.help);
+    }
+    #[test]
+    fn parse_help_test() {
+        assert!(parse(args(&["--help"])).unwrap().help);
+        assert!(parse(args(&["-h"])).unwrap().help);
+    }
+    #[test]
+    fn parse_values_test() {
+        let options = parse(args(&[
+            "--width",
+            "80",
+            "--height=24",
+            "--rule",
+            "B36/S23",
+            "--topology=klein-bottle",
+            "--neighborhood",
+            "extended-von-neumann:3",
+            "--generations",
+            "7",
+            "--delay=50",
+            "--pattern",
+            "glider.rle",
+            "--headless",
+        ]))
+        .unwrap();
+        assert_eq!((80, 24), (options.width, options.height));
+        assert_eq!(Some(Rule::HIGHLIFE), options.rule);
+        assert_eq!(Topology::KleinBottle, options.topology);
+        assert_eq!(Neighborhood::ExtendedVonNeumann(3), options.neighborhood);
+        assert_eq!(7, options.generations);
+        assert_eq!(Duration::from_millis(50), options.delay);
+        assert_eq!(Some(PathBuf::from("glider.rle")), options.pattern);
+        assert!(options.headless);
+    }
+    #[test]
+    fn parse_output_test() {
+        assert_eq!(Output::Grid, parse(args(&["--output", "grid"])).unwrap().output);
+        assert_eq!(Output::Population, parse(args(&["--output=population"])).unwrap().output);
+        assert!(parse(args(&["--output", "cells"])).unwrap_err().contains("invalid output 'cells'"));
+    }
+    #[test]
+    fn parse_size_error_test() {
+        for size in ["0", "-1", "wide", ""] {
+            let error = parse(args(&["--width", size])).unwrap_err();
+            assert_eq!(format!("--width expects a number of cells above 0, got '{size}'"), error);
+        }
+        assert!(parse(args(&["--height=0"])).is_err());
+    }
+    #[test]
+    fn parse_missing_value_test() {
+        assert_eq!(Err("--width needs a value".to_string()), parse(args(&["--width"])).map(|_| ()));
+        assert_eq!(Err("--rule needs a value".to_string()), parse(args(&["--headless", "--rule"])).map(|_| ()));
+    }
+    #[test]
+    fn parse_unknown_argument_test() {
+        assert_eq!(Err("unknown argument '--speed'".to_string()), parse(args(&["--speed=2"])).map(|_| ()));
+        assert_eq!(Err("unknown argument 'glider.rle'".to_string()), parse(args(&["glider.rle"])).map(|_| ()));
+    }
+    #[test]
+    fn parse_invalid_value_test() {
+        assert!(parse(args(&["--rule", "B9/S23"])).unwrap_err().starts_with("invalid rule 'B9/S23'"));
+        assert!(parse(args(&["--topology", "sphere"])).unwrap_err().starts_with("invalid topology 'sphere'"));
+        assert!(parse(args(&["--neighborhood", "moore:2"])).unwrap_err().starts_with("invalid neighborhood 'moore:2'"));
+        assert!(parse(args(&["--generations", "-3"])).is_err());
+    }
+    /// Tests extended neighborhoods only accept ranges from 1 up to the largest range of Larger than Life rules.
+    #[test]
+    fn parse_neighborhood_range_test() {
+        assert_eq!(Ok(Neighborhood::ExtendedMoore(1)), neighborhood("extended-moore:1"));
+        assert_eq!(Ok(Neighborhood::ExtendedMoore(MAX_RANGE)), neighborhood(&format!("extended-moore:{MAX_RANGE}")));
+        for range in ["0".to_string(), (MAX_RANGE + 1).to_string(), "far".to_string()] {
+            let error = neighborhood(&format!("extended-von-neumann:{range}")).unwrap_err();
+            assert!(error.starts_with(&format!("invalid range '{range}'")), "{error}");
+        }
+    }
+
+    // Helper Functions
+    // These are called in tests above.
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+}
@@ -0,0 +1,34 @@
+//! Reading and writing pattern files, in the format given by their extension.
+
+use std::{fs, path::Path};
+
+use game_of_life_core::pattern::{life, plaintext, rle, Pattern};
+
+/// Reads a pattern from a file.
+/// `.cells` and `.txt` files are plaintext, `.lif` and `.life` files are Life 1.05 or 1.06,
+/// and any other file is RLE.
+pub fn load(path: &Path) -> Result<Pattern, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {e}", path.display()))?;
+    let parsed = match extension(path).as_str() {
+        "cells" | "txt" => plaintext::parse(&text).map_err(|e| e.to_string()),
+        "lif" | "life" if text.starts_with("#Life 1.05") => life::parse_105(&text).map_err(|e| e.to_string()),
+        "lif" | "life" => life::parse_106(&text).map_err(|e| e.to_string()),
+        _ => rle::parse(&text).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|e| format!("could not parse {}: {e}", path.display()))
+}
+
+/// Writes a pattern to a file, in the same formats [`load`] reads.
+/// `.lif` and `.life` files are written as Life 1.06.
+pub fn save(path: &Path, pattern: &Pattern) -> Result<(), String> {
+    let text = match extension(path).as_str() {
+        "cells" | "txt" => plaintext::write(pattern),
+        "lif" | "life" => life::write_106(pattern),
+        _ => rle::write(pattern),
+    };
+    fs::write(path, text).map_err(|e| format!("could not write {}: {e}", path.display()))
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
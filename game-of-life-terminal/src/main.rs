@@ -1,35 +1,134 @@
-use std::io::stdin;
+use std::{
+    io::{stdin, stdout, IsTerminal},
+    process::exit,
+};
 
-use game_of_life_core::{grid::{obj::GameOfLifeGrid, Cell, Grid}, GameOfLife};
+use cli::{Options, Output};
+use game_of_life_core::{grid::{dynamic::DynGrid, neighborhood::Neighborhood, Cell, Grid}, pattern::Pattern, GameOfLife};
+
+mod cli;
+mod files;
+mod repl;
+mod screen;
 
 fn main() {
-    let mut input = String::new();
-    let mut game = GameOfLife::<GameOfLifeGrid<12, 12>>::default();
-    loop {
-        draw(game.grid());
-        input.clear();
-        stdin().read_line(&mut input);
-        input = input.trim_end().to_string();
-        if input.is_empty() {
-            game.next();
-        } else if let Some((x, y)) = input.split_once(' ') {
-            let x: usize = x.parse().unwrap();
-            let y: usize = y.parse().unwrap();
-            game.grid_mut().set(x, y, Cell::Alive).unwrap();
-            game.grid_mut().update();
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("error: {e}\n\nRun with --help to see the options.");
+            exit(2);
+        },
+    };
+    if options.help {
+        print!("{}", cli::USAGE);
+        return;
+    }
+    let mut game = match new_game(&options) {
+        Ok(game) => game,
+        Err(e) => {
+            eprintln!("error: {e}");
+            exit(1);
+        },
+    };
+    game.step(options.generations);
+    if options.headless {
+        match options.output {
+            Output::Grid => draw(game.grid(), game.neighborhood()),
+            Output::Population => println!("{}", game.grid().population()),
+        }
+        return;
+    }
+    // The full-screen interface needs a terminal to read keys from and draw on,
+    // otherwise commands are read from stdin line by line instead.
+    if stdin().is_terminal() && stdout().is_terminal() {
+        if let Err(e) = screen::run(&mut game, options.delay) {
+            eprintln!("error: {e}");
+            exit(1);
+        }
+        return;
+    }
+    repl::run(&mut game, stdin().lock());
+}
+
+/// Creates the game described by the options,
+/// with the pattern centered on the grid.
+fn new_game(options: &Options) -> Result<GameOfLife<DynGrid>, String> {
+    let mut grid = DynGrid::new(options.width, options.height).map_err(|e| e.to_string())?;
+    let mut rule = options.rule;
+    if let Some(path) = &options.pattern {
+        let pattern = files::load(path)?;
+        place(&pattern, &mut grid)?;
+        rule = rule.or(pattern.rule);
+    }
+    let game = GameOfLife::from_grid(grid)
+        .with_rule(rule.unwrap_or_default())
+        .and_then(|game| game.with_neighborhood(options.neighborhood.clone()))
+        .map_err(|e| e.to_string())?;
+    Ok(game.with_topology(options.topology))
+}
+
+/// Replaces every cell of the grid with the pattern, centered on the grid.
+/// Returns Err without changing the grid if the pattern does not fit.
+fn place(pattern: &Pattern, grid: &mut impl Grid) -> Result<(), String> {
+    let (width, height) = (grid.width(), grid.height());
+    if pattern.width() > width || pattern.height() > height {
+        return Err(format!(
+            "the pattern is {}x{} cells, which does not fit on a {width}x{height} grid",
+            pattern.width(),
+            pattern.height(),
+        ));
+    }
+    clear(grid);
+    // We know the pattern fits because we just checked its size.
+    pattern.write_to(grid, (width - pattern.width()) / 2, (height - pattern.height()) / 2).unwrap();
+    Ok(())
+}
+
+/// Kills every cell of the grid.
+fn clear(grid: &mut impl Grid) {
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            // We know the cell is in bounds
+            // because we use the grid's width and height
+            // for coordinates.
+            grid.set(x, y, Cell::Dead).unwrap();
         }
     }
+    grid.update();
+}
+
+/// Returns the character a cell is drawn with.
+/// Dying cells are drawn with how long they have been dying for,
+/// from `1` to `9`, and `+` past that.
+fn glyph(cell: Cell) -> &'static str {
+    const DYING: [&str; 10] = ["+", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+    match cell {
+        Cell::Dead => "O",
+        Cell::Alive => "X",
+        Cell::Dying(age) => DYING.get(age as usize).unwrap_or(&DYING[0]),
+    }
+}
+
+/// Returns the spaces drawn before row `y` and after each cell.
+/// Hexagonal grids are drawn with cells apart and each row half a cell
+/// to the right of the row below it, so each cell touches its six neighbors.
+fn spacing(neighborhood: &Neighborhood, height: usize, y: usize) -> (String, &'static str) {
+    match neighborhood {
+        Neighborhood::Hexagonal => (" ".repeat(height - 1 - y), " "),
+        _ => (String::new(), ""),
+    }
 }
 
-fn draw(grid: &impl Grid) {
+fn draw(grid: &impl Grid, neighborhood: &Neighborhood) {
     for y in 0..grid.height() {
+        let (indent, gap) = spacing(neighborhood, grid.height(), y);
+        print!("{indent}");
         for x in 0..grid.width() {
+            // We know the cell is in bounds
+            // because we use the grid's width and height
+            // for coordinates.
             let cell = grid.get(x, y).unwrap();
-            let cell = match cell {
-                Cell::Dead => "O",
-                Cell::Alive => "X",
-            };
-            print!("{cell}");
+            print!("{}{gap}", glyph(cell));
         }
         println!();
     }
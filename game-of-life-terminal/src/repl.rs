@@ -0,0 +1,240 @@
+//! A line based command language, for when the input is not a terminal.
+
+use std::{io::BufRead, path::Path};
+
+use game_of_life_core::{
+    grid::{Cell, CellOutOfBoundsError, Grid},
+    pattern::{soup::{Soup, Symmetry}, Pattern},
+    random::seed_of,
+    rule::Rule,
+    GameOfLife,
+};
+
+use crate::{clear, draw, files, place};
+
+pub const HELP: &str = "\
+Commands:
+  set X Y      Bring the cell at (X, Y) to life
+  kill X Y     Kill the cell at (X, Y)
+  toggle X Y   Bring the cell at (X, Y) to life if dead, or kill it if alive
+  clear        Kill every cell
+  soup D S SEED
+               Fill the grid at random, each cell alive with a probability of D,
+               with the symmetry S (C1, C2, C4, D2, D4 or D8), from the text SEED
+  run N        Run N generations, an empty line runs one
+  load FILE    Replace the grid with the pattern in FILE
+  save FILE    Save the grid to FILE
+  rule RULE    Change the rule, ex. rule B36/S23
+  help         Print this help
+  quit         Exit
+";
+
+#[derive(PartialEq, Debug)]
+enum Command {
+    Set(usize, usize, Cell),
+    Toggle(usize, usize),
+    Clear,
+    Soup(f64, Symmetry, String),
+    Run(u64),
+    Load(String),
+    Save(String),
+    Rule(Rule),
+    Help,
+    Quit,
+}
+
+/// Parses a line of input into a command.
+fn parse(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let command = match words.as_slice() {
+        [] => Command::Run(1),
+        ["set", x, y] => Command::Set(coordinate("x", x)?, coordinate("y", y)?, Cell::Alive),
+        ["kill", x, y] => Command::Set(coordinate("x", x)?, coordinate("y", y)?, Cell::Dead),
+        ["toggle", x, y] => Command::Toggle(coordinate("x", x)?, coordinate("y", y)?),
+        ["clear"] => Command::Clear,
+        ["soup", density, symmetry, seed] => Command::Soup(
+            match density.parse() {
+                Ok(density) if (0.0..=1.0).contains(&density) => density,
+                _ => return Err(format!("expected a density from 0 to 1, got '{density}'")),
+            },
+            symmetry.parse().map_err(|e| format!("{e}"))?,
+            seed.to_string(),
+        ),
+        ["run", n] => Command::Run(n.parse().map_err(|_| format!("expected a number of generations, got '{n}'"))?),
+        ["load", file] => Command::Load(file.to_string()),
+        ["save", file] => Command::Save(file.to_string()),
+        ["rule", rule] => Command::Rule(rule.parse().map_err(|e| format!("invalid rule '{rule}': {e}"))?),
+        ["help"] => Command::Help,
+        ["quit" | "exit"] => Command::Quit,
+        [name @ ("set" | "kill" | "toggle"), ..] => return Err(format!("usage: {name} X Y")),
+        ["soup", ..] => return Err("usage: soup DENSITY SYMMETRY SEED".to_string()),
+        [name @ ("run" | "load" | "save" | "rule"), ..] => return Err(format!("usage: {name} {}", name_of_argument(name))),
+        [name @ ("clear" | "help" | "quit" | "exit"), ..] => return Err(format!("{name} takes no arguments")),
+        [name, ..] => return Err(format!("unknown command '{name}', type help to see the commands")),
+    };
+    Ok(command)
+}
+
+fn name_of_argument(command: &str) -> &'static str {
+    match command {
+        "run" => "N",
+        "rule" => "RULE",
+        _ => "FILE",
+    }
+}
+
+fn coordinate(name: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("expected a whole number for {name}, got '{value}'"))
+}
+
+/// Describes a cell outside of the grid, along with the size of the grid.
+fn out_of_bounds(grid: &impl Grid, e: CellOutOfBoundsError) -> String {
+    format!("({}, {}) is outside of the {}x{} grid", e.x(), e.y(), grid.width(), grid.height())
+}
+
+/// Carries out a command, returning false once the program should exit.
+fn execute<T: Grid>(game: &mut GameOfLife<T>, command: Command) -> Result<bool, String> {
+    match command {
+        Command::Set(x, y, cell) => {
+            game.grid_mut().set(x, y, cell).map_err(|e| out_of_bounds(game.grid(), e))?;
+            game.grid_mut().update();
+        },
+        Command::Toggle(x, y) => {
+            let cell = game.grid().get(x, y).map_err(|e| out_of_bounds(game.grid(), e))?;
+            // We know the cell is in bounds because we just got it.
+            game.grid_mut().set(x, y, cell.flipped()).unwrap();
+            game.grid_mut().update();
+        },
+        Command::Clear => clear(game.grid_mut()),
+        Command::Soup(density, symmetry, seed) => {
+            let (width, height) = (game.grid().width(), game.grid().height());
+            let soup = Soup::new(width, height).with_density(density).with_symmetry(symmetry).map_err(|e| e.to_string())?;
+            // We know the soup fits because it is the size of the grid.
+            soup.write_to(game.grid_mut(), 0, 0, seed_of(&seed)).unwrap();
+        },
+        Command::Run(generations) => game.step(generations),
+        Command::Load(file) => {
+            let pattern = files::load(Path::new(&file))?;
+            place(&pattern, game.grid_mut())?;
+            if let Some(rule) = pattern.rule {
+                game.set_rule(rule).map_err(|e| e.to_string())?;
+            }
+        },
+        Command::Save(file) => {
+            let mut pattern = Pattern::from_grid(game.grid());
+            pattern.rule = Some(*game.rule());
+            files::save(Path::new(&file), &pattern)?;
+            println!("saved to {file}");
+            return Ok(true);
+        },
+        Command::Rule(rule) => {
+            game.set_rule(rule).map_err(|e| e.to_string())?;
+            println!("rule is now {rule}");
+            return Ok(true);
+        },
+        Command::Help => {
+            print!("{HELP}");
+            return Ok(true);
+        },
+        Command::Quit => return Ok(false),
+    }
+    draw(game.grid(), game.neighborhood());
+    Ok(true)
+}
+
+/// Reads commands from `input` until `quit` or the end of the input.
+pub fn run<T: Grid>(game: &mut GameOfLife<T>, mut input: impl BufRead) {
+    let mut line = String::new();
+    draw(game.grid(), game.neighborhood());
+    loop {
+        line.clear();
+        match input.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {},
+            Err(e) => {
+                eprintln!("error: {e}");
+                break;
+            },
+        }
+        match parse(&line).and_then(|command| execute(game, command)) {
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(e) => eprintln!("error: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life_core::grid::dynamic::DynGrid;
+
+    #[test]
+    fn parse_commands_test() {
+        assert_eq!(Ok(Command::Run(1)), parse(""));
+        assert_eq!(Ok(Command::Run(1)), parse("  \n"));
+        assert_eq!(Ok(Command::Set(3, 4, Cell::Alive)), parse("set 3 4"));
+        assert_eq!(Ok(Command::Set(0, 7, Cell::Dead)), parse("kill 0 7\n"));
+        assert_eq!(Ok(Command::Toggle(2, 1)), parse("toggle  2 1"));
+        assert_eq!(Ok(Command::Clear), parse("clear"));
+        assert_eq!(Ok(Command::Soup(0.5, Symmetry::D4, "abc".to_string())), parse("soup 0.5 d4 abc"));
+        assert_eq!(Ok(Command::Run(25)), parse("run 25"));
+        assert_eq!(Ok(Command::Load("glider.rle".to_string())), parse("load glider.rle"));
+        assert_eq!(Ok(Command::Save("out.cells".to_string())), parse("save out.cells"));
+        assert_eq!(Ok(Command::Rule(Rule::HIGHLIFE)), parse("rule B36/S23"));
+        assert_eq!(Ok(Command::Help), parse("help"));
+        assert_eq!(Ok(Command::Quit), parse("quit"));
+        assert_eq!(Ok(Command::Quit), parse("exit"));
+    }
+    #[test]
+    fn parse_usage_error_test() {
+        assert_eq!(Err("usage: set X Y".to_string()), parse("set 3"));
+        assert_eq!(Err("usage: kill X Y".to_string()), parse("kill"));
+        assert_eq!(Err("usage: toggle X Y".to_string()), parse("toggle 1 2 3"));
+        assert_eq!(Err("usage: soup DENSITY SYMMETRY SEED".to_string()), parse("soup 0.5 C1"));
+        assert_eq!(Err("usage: run N".to_string()), parse("run 1 2"));
+        assert_eq!(Err("usage: load FILE".to_string()), parse("load"));
+        assert_eq!(Err("usage: save FILE".to_string()), parse("save a b"));
+        assert_eq!(Err("usage: rule RULE".to_string()), parse("rule"));
+        assert_eq!(Err("clear takes no arguments".to_string()), parse("clear all"));
+        assert_eq!(Err("quit takes no arguments".to_string()), parse("quit now"));
+    }
+    #[test]
+    fn parse_invalid_argument_test() {
+        assert_eq!(Err("expected a whole number for x, got '-1'".to_string()), parse("set -1 2"));
+        assert_eq!(Err("expected a whole number for y, got 'b'".to_string()), parse("toggle 1 b"));
+        assert_eq!(Err("expected a number of generations, got 'many'".to_string()), parse("run many"));
+        assert_eq!(Err("expected a density from 0 to 1, got '1.5'".to_string()), parse("soup 1.5 C1 seed"));
+        assert_eq!(
+            Err("'E3' is not a symmetry, expected C1, C2, C4, D2, D4 or D8".to_string()),
+            parse("soup 0.5 E3 seed")
+        );
+        assert!(parse("rule B9/S23").unwrap_err().starts_with("invalid rule 'B9/S23'"));
+    }
+    #[test]
+    fn parse_unknown_command_test() {
+        assert_eq!(Err("unknown command 'jump', type help to see the commands".to_string()), parse("jump 1 2"));
+    }
+    /// Tests cells outside of the grid are reported along with the size of the grid,
+    /// without changing the grid.
+    #[test]
+    fn execute_out_of_bounds_test() {
+        let mut game = GameOfLife::from_grid(DynGrid::new(5, 3).unwrap());
+        let expected = Err("(5, 1) is outside of the 5x3 grid".to_string());
+        assert_eq!(expected, execute(&mut game, Command::Set(5, 1, Cell::Alive)));
+        assert_eq!(expected, execute(&mut game, Command::Toggle(5, 1)));
+        assert_eq!(0, game.grid().population());
+        assert_eq!(Ok(true), execute(&mut game, Command::Toggle(4, 2)));
+        assert_eq!(Ok(Cell::Alive), game.grid().get(4, 2));
+        assert_eq!(Ok(false), execute(&mut game, Command::Quit));
+    }
+    /// Tests the commands run until the end of the input, even without `quit`.
+    #[test]
+    fn run_until_end_of_input_test() {
+        let mut game = GameOfLife::from_grid(DynGrid::new(5, 3).unwrap());
+        run(&mut game, "set 1 1\nnot a command\ntoggle 2 1".as_bytes());
+        assert_eq!(2, game.grid().population());
+        run(&mut game, "".as_bytes());
+        assert_eq!(2, game.grid().population());
+    }
+}
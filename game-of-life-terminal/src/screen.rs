@@ -0,0 +1,217 @@
+//! A full-screen interface drawn with ANSI escape sequences,
+//! where cells are edited by moving a cursor over the grid.
+
+use std::{
+    io::{self, stdin, stdout, Read, Write},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use game_of_life_core::{grid::Grid, GameOfLife};
+
+use crate::{glyph, spacing};
+
+/// How many changes can be undone.
+const HISTORY: usize = 1000;
+
+const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?1049l";
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const HOME: &str = "\x1b[H";
+const CLEAR_LINE: &str = "\x1b[K";
+const CLEAR_BELOW: &str = "\x1b[J";
+const REVERSE: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+const HELP: &str = "arrows/hjkl move  space toggle  n step  p play/pause  u undo  r redo  q quit";
+
+/// Puts the terminal into raw mode for as long as it lives,
+/// restoring the previous mode when dropped, even if the program panics.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        // Reads wait at most a tenth of a second,
+        // so the game can keep playing while no key is pressed.
+        stty(&["raw", "-echo", "min", "0", "time", "1"])?;
+        print!("{ENTER_ALTERNATE_SCREEN}{HIDE_CURSOR}");
+        stdout().flush()?;
+        Ok(RawMode { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("{SHOW_CURSOR}{LEAVE_ALTERNATE_SCREEN}");
+        let _ = stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// Runs `stty` on the terminal of stdin, returning what it printed.
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Char(char),
+}
+
+/// Splits the bytes read from the terminal into keys,
+/// where arrow keys arrive as `ESC [ A` through `ESC [ D`.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i..] {
+            [0x1b, b'[', arrow, ..] => {
+                match arrow {
+                    b'A' => keys.push(Key::Up),
+                    b'B' => keys.push(Key::Down),
+                    b'C' => keys.push(Key::Right),
+                    b'D' => keys.push(Key::Left),
+                    _ => {},
+                }
+                i += 3;
+            },
+            [byte, ..] => {
+                keys.push(Key::Char(byte as char));
+                i += 1;
+            },
+            [] => break,
+        }
+    }
+    keys
+}
+
+/// Runs the game full-screen until `q` is pressed,
+/// waiting `delay` between generations while playing.
+pub fn run<T: Grid>(game: &mut GameOfLife<T>, delay: Duration) -> io::Result<()> {
+    let _raw = RawMode::enable()?;
+    game.set_history_capacity(HISTORY);
+    let (mut x, mut y) = (0, 0);
+    let mut playing = false;
+    let mut last_step = Instant::now();
+    let mut buffer = [0; 64];
+    let mut redraw = true;
+    loop {
+        if redraw {
+            draw(game, x, y, playing)?;
+        }
+        let read = stdin().read(&mut buffer)?;
+        redraw = read > 0;
+        for key in parse_keys(&buffer[..read]) {
+            let (width, height) = (game.grid().width(), game.grid().height());
+            match key {
+                Key::Up | Key::Char('k') => y = y.saturating_sub(1),
+                Key::Down | Key::Char('j') => y = (y + 1).min(height.saturating_sub(1)),
+                Key::Left | Key::Char('h') => x = x.saturating_sub(1),
+                Key::Right | Key::Char('l') => x = (x + 1).min(width.saturating_sub(1)),
+                Key::Char(' ') => {
+                    if let Ok(cell) = game.grid().get(x, y) {
+                        // We know the cell is in bounds because we just got it.
+                        game.grid_mut().set(x, y, cell.flipped()).unwrap();
+                        game.grid_mut().update();
+                    }
+                },
+                Key::Char('n') => game.next(),
+                Key::Char('p') => {
+                    playing = !playing;
+                    last_step = Instant::now();
+                },
+                Key::Char('u') => {
+                    game.undo();
+                },
+                Key::Char('r') => {
+                    game.redo();
+                },
+                // Ctrl-C arrives as a byte in raw mode.
+                Key::Char('q') | Key::Char('\x03') => return Ok(()),
+                Key::Char(_) => {},
+            }
+        }
+        if playing && last_step.elapsed() >= delay {
+            game.next();
+            last_step = Instant::now();
+            redraw = true;
+        }
+    }
+}
+
+/// Redraws the screen in place, with the cell under the cursor highlighted.
+fn draw<T: Grid>(game: &GameOfLife<T>, cursor_x: usize, cursor_y: usize, playing: bool) -> io::Result<()> {
+    let grid = game.grid();
+    let mut frame = String::from(HOME);
+    for y in 0..grid.height() {
+        let (indent, gap) = spacing(game.neighborhood(), grid.height(), y);
+        frame += &indent;
+        for x in 0..grid.width() {
+            // We know the cell is in bounds
+            // because we use the grid's width and height
+            // for coordinates.
+            let cell = glyph(grid.get(x, y).unwrap());
+            if (x, y) == (cursor_x, cursor_y) {
+                frame += &format!("{REVERSE}{cell}{RESET}{gap}");
+            } else {
+                frame += &format!("{cell}{gap}");
+            }
+        }
+        frame += &format!("{CLEAR_LINE}\r\n");
+    }
+    let state = if playing { "playing" } else { "paused" };
+    frame += &format!(
+        "generation {}  population {}  ({cursor_x}, {cursor_y})  {state}{CLEAR_LINE}\r\n{HELP}{CLEAR_LINE}\r\n{CLEAR_BELOW}",
+        game.generation(),
+        grid.population(),
+    );
+    let mut stdout = stdout().lock();
+    stdout.write_all(frame.as_bytes())?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_arrow_keys_test() {
+        assert_eq!(vec![Key::Up, Key::Down, Key::Right, Key::Left], parse_keys(b"\x1b[A\x1b[B\x1b[C\x1b[D"));
+    }
+    #[test]
+    fn parse_letter_keys_test() {
+        let keys = parse_keys(b"hjkl np");
+        let expected: Vec<Key> = "hjkl np".chars().map(Key::Char).collect();
+        assert_eq!(expected, keys);
+    }
+    #[test]
+    fn parse_mixed_keys_test() {
+        assert_eq!(vec![Key::Char('n'), Key::Up, Key::Char(' ')], parse_keys(b"n\x1b[A "));
+        assert!(parse_keys(b"").is_empty());
+    }
+    /// Tests escape sequences other than arrows are skipped whole.
+    #[test]
+    fn parse_unknown_escape_test() {
+        assert_eq!(vec![Key::Char('q')], parse_keys(b"\x1b[Zq"));
+    }
+    /// Tests an escape sequence split across reads becomes separate characters
+    /// rather than being dropped or read past the end.
+    #[test]
+    fn parse_split_escape_test() {
+        assert_eq!(vec![Key::Char('\x1b')], parse_keys(b"\x1b"));
+        assert_eq!(vec![Key::Char('\x1b'), Key::Char('[')], parse_keys(b"\x1b["));
+        assert_eq!(vec![Key::Char('A')], parse_keys(b"A"));
+    }
+}